@@ -1,5 +1,6 @@
+use std::collections::HashMap;
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use image::{ImageBuffer, Pixel};
 
@@ -30,6 +31,7 @@ where
 pub struct AssetManager {
     tex_storage: Vec<RawImageInfo>,
     tex_count: usize,
+    loaded: HashMap<PathBuf, TextureInfo>,
 }
 
 impl AssetManager {
@@ -37,6 +39,7 @@ impl AssetManager {
         AssetManager {
             tex_storage: vec![],
             tex_count: 0,
+            loaded: HashMap::new(),
         }
     }
 
@@ -44,20 +47,25 @@ impl AssetManager {
         &mut self,
         path: P,
     ) -> Result<TextureInfo, image::ImageError> {
+        if let Some(info) = self.loaded.get(path.as_ref()) {
+            return Ok(*info);
+        }
         println!("Loading texture ({})", path.as_ref().display(),);
-        let img = image::open(path).map(|img| img.to_rgb())?;
+        let img = image::open(&path).map(|img| img.to_rgba())?;
         let (width, height) = img.dimensions();
 
         self.tex_storage.push(RawImageInfo::from(img));
         let id = self.tex_count;
         self.tex_count += 1;
 
-        Ok(TextureInfo::new(id, width, height))
+        let info = TextureInfo::new(id, width, height);
+        self.loaded.insert(path.as_ref().to_path_buf(), info);
+        Ok(info)
     }
 
     pub fn upload_textures<F>(&mut self, mut callback: F)
     where
-        F: FnMut(u32, u32, Vec<u8>) -> (),
+        F: FnMut(u32, u32, Vec<u8>),
     {
         for raw in self.tex_storage.drain(..) {
             callback(raw.width, raw.height, raw.data);
@@ -1,11 +1,91 @@
+use std::path::Path;
+
 use specs::prelude::*;
+use specs::shrev::EventChannel;
 
 use crate::components::{Ball, Block, Paddle};
 use crate::game_error::GameError;
+use crate::high_scores::{HighScoreEntry, HighScores};
+use crate::level::Level;
+use crate::resources::{Effects, Levels, Lives, Score};
+use crate::types::GameEvent;
+use crate::util::today;
+
+pub fn init(world: &mut World, levels: Levels) -> Result<(), GameError> {
+    world.insert(levels);
+    start_level(world)
+}
 
-pub fn init(world: &mut World) -> Result<(), GameError> {
-    Paddle::init(world);
+/// Spawns the entities of the current level.
+fn start_level(world: &mut World) -> Result<(), GameError> {
+    let (path, number) = {
+        let levels = world.fetch::<Levels>();
+        (levels.current_path().to_path_buf(), levels.number())
+    };
+
+    let level = Level::load(path)?;
+    world.insert(Effects::default());
+    Paddle::init(world, level.paddle_width);
     Ball::init(world);
-    Block::init(world);
+    Block::init(world, &level);
+
+    world
+        .fetch_mut::<EventChannel<GameEvent>>()
+        .single_write(GameEvent::LevelStarted(number));
+    Ok(())
+}
+
+/// Tears down the finished level and starts the next one. When there is no
+/// next level `AllLevelsComplete` is sent instead.
+pub fn next_level(world: &mut World) -> Result<(), GameError> {
+    world.delete_all();
+    world.maintain();
+
+    if !world.fetch_mut::<Levels>().advance() {
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(GameEvent::AllLevelsComplete);
+        return Ok(());
+    }
+
+    start_level(world)
+}
+
+/// Throws away the current game and starts over from the first level.
+pub fn new_game(world: &mut World) -> Result<(), GameError> {
+    world.delete_all();
+    world.maintain();
+
+    world.fetch_mut::<Levels>().restart();
+    world.insert(Lives::default());
+    world.insert(Score::default());
+
+    start_level(world)
+}
+
+/// Enters the final score into the high score table at `path`.
+pub fn record_high_score(world: &World, path: &Path, name: &str) -> Result<(), GameError> {
+    let entry = HighScoreEntry {
+        name: name.to_string(),
+        score: world.fetch::<Score>().points,
+        level: world.fetch::<Levels>().number(),
+        date: today(),
+    };
+
+    let mut scores = HighScores::load(path)?;
+    if let Some(rank) = scores.insert(entry) {
+        println!("New high score, rank {}!", rank + 1);
+        scores.save(path)?;
+    }
+    for (i, e) in scores.entries().iter().enumerate() {
+        println!(
+            "{:>2}. {:<16} {:>8}  level {:<3} {}",
+            i + 1,
+            e.name,
+            e.score,
+            e.level,
+            e.date
+        );
+    }
     Ok(())
 }
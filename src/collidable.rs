@@ -1,113 +1,357 @@
-use cgmath::Point2;
+use cgmath::{InnerSpace, Vector2};
 
 use crate::components::Transform;
-use crate::types::OverlapType;
+use crate::types::{Contact, Penetration};
 
-pub trait Collidable {
-    fn get_hitbox(&self) -> ((f32, f32), (f32, f32));
-    fn intersects<C: Collidable>(
-        &self,
-        other: &C,
-        center: &Transform,
-        other_center: &Transform,
-    ) -> OverlapType {
-        let ((x0, y0), (x1, y1)) = self.corners(center);
-        let ((a0, b0), (a1, b1)) = other.corners(other_center);
-
-        if x0 > a1 || a0 > x1 {
-            return OverlapType::None;
-        }
+/// The outline of something that can be collided with, centered on its
+/// `Transform`'s position and measured in world units.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    Aabb {
+        half_width: f32,
+        half_height: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+    /// A horizontal bar with rounded ends: the points within `radius` of a
+    /// line running `half_length` either side of the center.
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+}
 
-        if y0 > b1 || b0 > y1 {
-            return OverlapType::None;
+impl Shape {
+    /// Every shape here is a box with its edges pushed out by a radius: a box
+    /// has no radius, a circle has no box, and a capsule's box has no height.
+    fn core(&self) -> (Vector2<f32>, f32) {
+        match *self {
+            Shape::Aabb {
+                half_width,
+                half_height,
+            } => (Vector2::new(half_width, half_height), 0.0),
+            Shape::Circle { radius } => (Vector2::new(0.0, 0.0), radius),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => (Vector2::new(half_length, 0.0), radius),
         }
+    }
+
+    /// How far the shape reaches from its center in `direction`, which has
+    /// to be a unit vector.
+    pub fn reach(&self, direction: Vector2<f32>) -> f32 {
+        let (core, radius) = self.core();
+        direction.x.abs() * core.x + direction.y.abs() * core.y + radius
+    }
+
+    /// Half the size of the smallest box around the shape.
+    pub fn half_extents(&self) -> Vector2<f32> {
+        let (core, radius) = self.core();
+        core + Vector2::new(radius, radius)
+    }
+}
+
+pub trait Collidable {
+    fn shape(&self) -> Shape;
+
+    /// Lower left and upper right corners of the box around the shape when it
+    /// is centered on `center`, in world units.
+    fn bounds(&self, center: &Transform) -> ((f32, f32), (f32, f32)) {
+        let c = center.position;
+        let h = self.shape().half_extents();
+        ((c.x - h.x, c.y - h.y), (c.x + h.x, c.y + h.y))
+    }
+}
 
-        let c = center.as_screen_point();
+/// The shape swept out by `b` as `a` moves around it, as a box and a radius
+/// centered on `b`. Collisions between `a` and `b` then come down to whether
+/// `a`'s center is inside it.
+fn minkowski(a: &Shape, b: &Shape) -> (Vector2<f32>, f32) {
+    let (core_a, radius_a) = a.core();
+    let (core_b, radius_b) = b.core();
+    (core_a + core_b, radius_a + radius_b)
+}
+
+/// Finds how far `a` at `a_pos` has sunk into `b` at `b_pos`, and which way
+/// to move `a` to separate them with the least movement. Shapes that only
+/// touch don't overlap.
+pub fn penetration(
+    a: &Shape,
+    a_pos: Vector2<f32>,
+    b: &Shape,
+    b_pos: Vector2<f32>,
+) -> Option<Penetration> {
+    let (half, radius) = minkowski(a, b);
+    let p = a_pos - b_pos;
 
-        let x_coll = (c.x <= a0 || c.x >= a1) && (b0 <= c.y && c.y <= b1);
-        let y_coll = (c.y <= b0 || c.y >= b1) && (a0 <= c.x && c.x <= a1);
+    let closest = Vector2::new(p.x.clamp(-half.x, half.x), p.y.clamp(-half.y, half.y));
+    let outside = p - closest;
+    if outside != Vector2::new(0.0, 0.0) {
+        let distance = outside.magnitude();
+        if distance >= radius {
+            return None;
+        }
+        return Some(Penetration {
+            normal: outside / distance,
+            depth: radius - distance,
+        });
+    }
+
+    // The center is inside the box, so leave by the nearest side
+    let dx = half.x - p.x.abs();
+    let dy = half.y - p.y.abs();
+    if dx.min(dy) + radius <= 0.0 {
+        return None;
+    }
+    if dx < dy {
+        Some(Penetration {
+            normal: Vector2::new(p.x.signum(), 0.0),
+            depth: dx + radius,
+        })
+    } else {
+        Some(Penetration {
+            normal: Vector2::new(0.0, p.y.signum()),
+            depth: dy + radius,
+        })
+    }
+}
 
-        match (x_coll, y_coll) {
-            (true, false) => OverlapType::OnlyX,
-            (false, true) => OverlapType::OnlyY,
-            _ => OverlapType::Both,
+/// Entry and exit times of a point moving by `d` from `c` through the slab
+/// between `lo` and `hi`, or `None` if it never enters it.
+fn slab(c: f32, d: f32, lo: f32, hi: f32) -> Option<(f32, f32)> {
+    if d == 0.0 {
+        if c <= lo || c >= hi {
+            return None;
         }
+        return Some((f32::NEG_INFINITY, f32::INFINITY));
     }
+    let t0 = (lo - c) / d;
+    let t1 = (hi - c) / d;
+    Some((t0.min(t1), t0.max(t1)))
+}
 
-    fn corners(&self, center: &Transform) -> ((f32, f32), (f32, f32)) {
-        let (bl, tr) = self.get_hitbox();
-        let c: Point2<f32> = center.as_screen_point();
+/// Where the point `p` moving by `motion` enters the box of half size `half`
+/// around the origin.
+fn sweep_box(p: Vector2<f32>, motion: Vector2<f32>, half: Vector2<f32>) -> Option<Contact> {
+    let (tx0, tx1) = slab(p.x, motion.x, -half.x, half.x)?;
+    let (ty0, ty1) = slab(p.y, motion.y, -half.y, half.y)?;
 
-        (
-            (c.x - bl.0.abs(), c.y - bl.1.abs()),
-            (c.x + tr.0.abs(), c.y + tr.1.abs()),
-        )
+    let entry = tx0.max(ty0);
+    let exit = tx1.min(ty1);
+    if entry >= exit || !(0.0..=1.0).contains(&entry) {
+        return None;
     }
+
+    let normal = if tx0 > ty0 {
+        Vector2::new(-motion.x.signum(), 0.0)
+    } else {
+        Vector2::new(0.0, -motion.y.signum())
+    };
+    Some(Contact {
+        time: entry,
+        normal,
+    })
+}
+
+/// Where the point `p` moving by `motion` enters the circle of `radius`
+/// around `center`.
+fn sweep_circle(
+    p: Vector2<f32>,
+    motion: Vector2<f32>,
+    center: Vector2<f32>,
+    radius: f32,
+) -> Option<Contact> {
+    let m = p - center;
+    let a = motion.magnitude2();
+    let b = m.dot(motion);
+    let c = m.magnitude2() - radius * radius;
+    if a == 0.0 || c < 0.0 || b >= 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / a;
+    if !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+    Some(Contact {
+        time,
+        normal: (m + time * motion) / radius,
+    })
+}
+
+/// Moves `a` from `a_pos` by `motion` and finds the first point where it
+/// touches `b` at `b_pos`. Shapes that already overlap are left to
+/// `penetration`.
+pub fn sweep(
+    a: &Shape,
+    a_pos: Vector2<f32>,
+    motion: Vector2<f32>,
+    b: &Shape,
+    b_pos: Vector2<f32>,
+) -> Option<Contact> {
+    let (half, radius) = minkowski(a, b);
+    let p = a_pos - b_pos;
+
+    // The flat sides, pushed out by the radius...
+    let sides = [
+        sweep_box(p, motion, half + Vector2::new(radius, 0.0)),
+        sweep_box(p, motion, half + Vector2::new(0.0, radius)),
+    ];
+    // ...and the rounded corners between them
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+        .iter()
+        .filter(|_| radius > 0.0)
+        .map(|&(x, y)| sweep_circle(p, motion, Vector2::new(x * half.x, y * half.y), radius));
+
+    sides
+        .iter()
+        .copied()
+        .chain(corners)
+        .flatten()
+        .min_by(|a, b| a.time.total_cmp(&b.time))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::{Sprite, Transform};
-    use crate::types::TextureInfo;
+    use crate::components::Collider;
+
+    fn aabb(half_width: f32, half_height: f32) -> Shape {
+        Shape::Aabb {
+            half_width,
+            half_height,
+        }
+    }
+
+    fn v(x: f32, y: f32) -> Vector2<f32> {
+        Vector2::new(x, y)
+    }
 
     #[test]
-    fn test_sprite_hitbox_intersect_corner() {
-        let t1 = Transform::default().with_pos((0.0, 0.0));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s1 = Sprite::new(&tex, (0, 0), (100, 50));
-        s1.offsets = [0.5, 0.5];
+    fn test_shape_bounds() {
+        let t = Transform::default().with_pos((10.0, 5.0));
+        let capsule = Collider::new(Shape::Capsule {
+            half_length: 2.0,
+            radius: 0.5,
+        });
+        assert_eq!(capsule.bounds(&t), ((7.5, 4.5), (12.5, 5.5)));
+        assert_eq!(
+            Collider::new(aabb(1.0, 2.0)).bounds(&t),
+            ((9.0, 3.0), (11.0, 7.0))
+        );
 
-        let t2 = Transform::default().with_pos((100., 50.));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s2 = Sprite::new(&tex, (0, 0), (100, 50));
-        s2.offsets = [0.5, 0.5];
+        let circle = Shape::Circle { radius: 0.5 };
+        assert_eq!(circle.reach(v(1., 1.).normalize()), 0.5);
+        assert_eq!(aabb(1.0, 2.0).reach(v(0., -1.)), 2.0);
+    }
+
+    #[test]
+    fn test_sweep_hits_face() {
+        let ball = aabb(5., 5.);
+        let wall = aabb(5., 50.);
 
-        assert_eq!(s1.intersects(&s2, &t1, &t2), OverlapType::Both);
+        let contact = sweep(&ball, v(5., 5.), v(200., 0.), &wall, v(105., 0.)).unwrap();
+        assert!((contact.time - 0.45).abs() < 1e-6);
+        assert_eq!(contact.normal, v(-1., 0.));
+
+        assert_eq!(
+            sweep(&ball, v(5., 5.), v(50., 0.), &wall, v(105., 0.)),
+            None
+        );
+        assert_eq!(
+            sweep(&ball, v(5., 5.), v(200., 200.), &wall, v(105., 0.)),
+            None
+        );
     }
 
     #[test]
-    fn test_sprite_hitbox_intersect_x() {
-        let t1 = Transform::default().with_pos((0.0, 0.0));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s1 = Sprite::new(&tex, (0, 0), (100, 50));
-        s1.offsets = [0.5, 0.5];
+    fn test_sweep_does_not_tunnel() {
+        // Far enough in one step to end up entirely on the other side
+        let ball = Shape::Circle { radius: 5. };
+        let block = aabb(20., 5.);
+
+        let contact = sweep(&ball, v(5., 105.), v(0., -200.), &block, v(0., 55.)).unwrap();
+        assert!((contact.time - 0.2).abs() < 1e-6);
+        assert_eq!(contact.normal, v(0., 1.));
+    }
 
-        let t2 = Transform::default().with_pos((100., 0.));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s2 = Sprite::new(&tex, (0, 0), (100, 50));
-        s2.offsets = [0.5, 0.5];
+    #[test]
+    fn test_sweep_circle_round_corner() {
+        let ball = Shape::Circle { radius: 1. };
+        let block = aabb(1., 1.);
 
-        assert_eq!(s1.intersects(&s2, &t1, &t2), OverlapType::OnlyX);
+        // Slips past the corner of the block, which a box would have clipped
+        assert_eq!(
+            sweep(&ball, v(0.8, 2.8), v(2., -2.), &block, v(0., 0.)),
+            None
+        );
+        assert!(sweep(&aabb(1., 1.), v(0.8, 2.8), v(2., -2.), &block, v(0., 0.)).is_some());
+
+        // Strikes the corner square on, and bounces straight back
+        let d = 1.0 + 0.5_f32.sqrt();
+        let contact = sweep(&ball, v(-3., -3.), v(2., 2.), &block, v(0., 0.)).unwrap();
+        assert!((contact.time - (3.0 - d) / 2.0).abs() < 1e-5);
+        assert!((contact.normal - v(-1., -1.).normalize()).magnitude() < 1e-5);
     }
 
     #[test]
-    fn test_sprite_hitbox_intersect_y() {
-        let t1 = Transform::default().with_pos((0.0, 0.0));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s1 = Sprite::new(&tex, (0, 0), (100, 50));
-        s1.offsets = [0.5, 0.5];
+    fn test_sweep_leaves_overlaps_alone() {
+        let ball = aabb(5., 5.);
+        let paddle = aabb(50., 5.);
 
-        let t2 = Transform::default().with_pos((0., 50.));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s2 = Sprite::new(&tex, (0, 0), (100, 50));
-        s2.offsets = [0.5, 0.5];
+        assert_eq!(
+            sweep(&ball, v(5., 10.), v(0., -5.), &paddle, v(0., 5.)),
+            None
+        );
+        assert_eq!(
+            sweep(&ball, v(5., 10.), v(0., 5.), &paddle, v(0., 5.)),
+            None
+        );
+    }
 
-        assert_eq!(s1.intersects(&s2, &t1, &t2), OverlapType::OnlyY);
+    #[test]
+    fn test_penetration() {
+        let ball = aabb(5., 5.);
+        let paddle = aabb(50., 5.);
+
+        // Sunk into the top
+        let p = penetration(&ball, v(5., 12.), &paddle, v(0., 5.)).unwrap();
+        assert_eq!(p.normal, v(0., 1.));
+        assert_eq!(p.depth, 3.);
+
+        // Caught by the end of the paddle as it moved
+        let p = penetration(&ball, v(-53., 7.), &paddle, v(0., 5.)).unwrap();
+        assert_eq!(p.normal, v(-1., 0.));
+        assert_eq!(p.depth, 2.);
+
+        assert_eq!(penetration(&ball, v(5., 15.), &paddle, v(0., 5.)), None);
     }
 
     #[test]
-    fn test_sprite_hitbox_no_intersect() {
-        let t1 = Transform::default().with_pos((0.0, 0.0));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s1 = Sprite::new(&tex, (0, 0), (100, 50));
-        s1.offsets = [0.5, 0.5];
-
-        let t2 = Transform::default().with_pos((200., 100.));
-        let tex = TextureInfo::new(0, 100, 50);
-        let mut s2 = Sprite::new(&tex, (0, 0), (100, 50));
-        s2.offsets = [0.5, 0.5];
-
-        assert_eq!(s1.intersects(&s2, &t1, &t2), OverlapType::None);
+    fn test_penetration_circle() {
+        let ball = Shape::Circle { radius: 1. };
+        let block = aabb(1., 1.);
+
+        // Off the corner, but within reach of it
+        let p = penetration(&ball, v(1.5, 1.5), &block, v(0., 0.)).unwrap();
+        assert!((p.normal - v(1., 1.).normalize()).magnitude() < 1e-6);
+        assert!((p.depth - (1.0 - 0.5_f32.sqrt())).abs() < 1e-6);
+
+        // A box would overlap here, but the circle is clear of the corner
+        assert_eq!(penetration(&ball, v(1.9, 1.9), &block, v(0., 0.)), None);
+
+        let paddle = Shape::Capsule {
+            half_length: 2.,
+            radius: 0.5,
+        };
+        let p = penetration(&ball, v(0., 1.), &paddle, v(0., 0.)).unwrap();
+        assert_eq!(p.normal, v(0., 1.));
+        assert_eq!(p.depth, 0.5);
     }
 }
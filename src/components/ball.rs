@@ -1,9 +1,10 @@
-use cgmath::{InnerSpace, Vector2};
+use cgmath::Vector2;
 use specs::prelude::*;
 
 use crate::asset_manager::AssetManager;
-use crate::components::{Sprite, Transform};
-use crate::constants::{BALL_SPEED, WORLD_HEIGHT};
+use crate::collidable::Shape;
+use crate::components::{Collider, Paddle, Sprite, Transform};
+use crate::constants::{BALL_REST_HEIGHT, PIXELS_PER_UNIT};
 
 pub struct Ball {
     pub velocity: Vector2<f32>,
@@ -13,12 +14,33 @@ impl Component for Ball {
     type Storage = HashMapStorage<Self>;
 }
 
+/// A ball resting on the paddle, `offset` world units right of its middle.
+/// It follows the paddle around until it is launched.
+pub struct Stuck {
+    pub offset: f32,
+}
+
+impl Component for Stuck {
+    type Storage = HashMapStorage<Self>;
+}
+
 impl Ball {
+    /// Puts a ball on the paddle, ready to be launched.
     pub fn init(world: &mut World) {
+        let start = {
+            let paddles = world.read_storage::<Paddle>();
+            let transforms = world.read_storage::<Transform>();
+            (&paddles, &transforms)
+                .join()
+                .map(|(_, t)| t.position)
+                .next()
+                .expect("The paddle goes in before the ball")
+        } + Vector2::new(0., BALL_REST_HEIGHT);
+
         let global_t = {
             Transform::default()
                 .with_pos((100., 0.))
-                .with_scale((15., 15.))
+                .with_scale((PIXELS_PER_UNIT, PIXELS_PER_UNIT))
         };
 
         let tex_info = {
@@ -28,15 +50,20 @@ impl Ball {
                 .expect("Failed to load ball texture")
         };
 
-        let initial_dir = (Vector2::unit_x() - Vector2::unit_y()).normalize();
-
         let ball = Ball {
-            velocity: initial_dir * BALL_SPEED,
+            velocity: Vector2::new(0., 0.),
         };
         let mut s1 = Sprite::new(&tex_info, (0, 0), (15, 15));
         s1.offsets = [0.5, 0.5];
-        let mut t1 = Transform::default().with_pos((0.5, WORLD_HEIGHT / 2.0));
+        let mut t1 = Transform::default().with_pos(start);
         t1.global = global_t.matrix();
-        world.create_entity().with(s1).with(t1).with(ball).build();
+        world
+            .create_entity()
+            .with(s1)
+            .with(t1)
+            .with(ball)
+            .with(Stuck { offset: 0. })
+            .with(Collider::new(Shape::Circle { radius: 0.5 }))
+            .build();
     }
 }
@@ -1,22 +1,87 @@
 use specs::prelude::*;
 
 use crate::asset_manager::AssetManager;
-use crate::components::{IsCollidable, Sprite, Transform};
-use crate::constants::{WORLD_HEIGHT, WORLD_WIDTH};
+use crate::collidable::Shape;
+use crate::components::{Collider, Sprite, Transform};
+use crate::constants::{BLOCK_POINTS_PER_HIT, PIXELS_PER_UNIT, WORLD_HEIGHT};
+use crate::level::Level;
+use crate::types::TextureInfo;
 
-#[derive(Default)]
-pub struct Block;
+/// Size of one block in `block.png`. The texture is a strip of them: the
+/// undamaged block, then increasingly cracked ones, then the indestructible
+/// block.
+const FRAME_SIZE: (u32, u32) = (30, 15);
+const DAMAGE_FRAMES: u32 = 3;
+const INDESTRUCTIBLE_FRAME: u32 = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockKind {
+    Standard,
+    Indestructible,
+}
+
+pub struct Block {
+    pub kind: BlockKind,
+    pub hit_points: u32,
+    max_hit_points: u32,
+    /// Awarded when the block is destroyed.
+    pub points: u32,
+}
 
 impl Component for Block {
-    type Storage = NullStorage<Self>;
+    type Storage = DenseVecStorage<Self>;
 }
 
 impl Block {
-    pub fn init(world: &mut World) {
+    pub fn new(kind: BlockKind, hit_points: u32) -> Self {
+        Block {
+            kind,
+            hit_points,
+            max_hit_points: hit_points,
+            points: hit_points * BLOCK_POINTS_PER_HIT,
+        }
+    }
+
+    /// Whether the block has to be destroyed to finish the level.
+    pub fn is_destructible(&self) -> bool {
+        self.kind != BlockKind::Indestructible
+    }
+
+    /// Applies one hit and returns whether the block is destroyed by it.
+    pub fn hit(&mut self) -> bool {
+        match self.kind {
+            BlockKind::Indestructible => false,
+            BlockKind::Standard => {
+                self.hit_points = self.hit_points.saturating_sub(1);
+                self.hit_points == 0
+            }
+        }
+    }
+
+    /// Which block in the texture to show for the damage taken so far.
+    pub fn frame(&self) -> u32 {
+        match self.kind {
+            BlockKind::Indestructible => INDESTRUCTIBLE_FRAME,
+            BlockKind::Standard => {
+                let damage = self.max_hit_points - self.hit_points;
+                (damage * DAMAGE_FRAMES / self.max_hit_points.max(1)).min(DAMAGE_FRAMES - 1)
+            }
+        }
+    }
+
+    pub fn sprite(&self, texture: &TextureInfo) -> Sprite {
+        let (w, h) = FRAME_SIZE;
+        let left = self.frame() * w;
+        let mut sprite = Sprite::new(texture, (left, 0), (left + w, h));
+        sprite.offsets = [0.5, 0.5];
+        sprite
+    }
+
+    pub fn init(world: &mut World, level: &Level) {
         let global_t = {
             Transform::default()
                 .with_pos((100., 0.))
-                .with_scale((15., 15.))
+                .with_scale((PIXELS_PER_UNIT, PIXELS_PER_UNIT))
         };
         let tex_info = {
             let mut asset_manager = world.fetch_mut::<AssetManager>();
@@ -25,20 +90,50 @@ impl Block {
                 .expect("Failed to load block texture")
         };
 
-        let mut s1 = Sprite::new(&tex_info, (0, 0), (30, 15));
-        s1.offsets = [0.5, 0.5];
-        for x in 0..(WORLD_WIDTH / 2.0) as u32 {
-            for y in (WORLD_HEIGHT - 4.) as u32..WORLD_HEIGHT as u32 {
-                let mut t1 = Transform::default().with_pos((2.0 * x as f32 + 0.5, y as f32 - 0.5));
-                t1.global = global_t.matrix();
-                world
-                    .create_entity()
-                    .with(s1)
-                    .with(t1)
-                    .with(Block)
-                    .with(IsCollidable)
-                    .build();
-            }
+        for spec in &level.blocks {
+            let mut t1 = Transform::default().with_pos((
+                2.0 * spec.column as f32 + 0.5,
+                WORLD_HEIGHT - 1.5 - spec.row as f32,
+            ));
+            t1.global = global_t.matrix();
+            let block = Block::new(spec.kind, spec.hit_points);
+            world
+                .create_entity()
+                .with(block.sprite(&tex_info))
+                .with(t1)
+                .with(block)
+                .with(Collider::new(Shape::Aabb {
+                    half_width: 1.0,
+                    half_height: 0.5,
+                }))
+                .build();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_damage_frames() {
+        let mut block = Block::new(BlockKind::Standard, 3);
+        assert_eq!(block.points, 30);
+
+        let mut frames = vec![block.frame()];
+        while !block.hit() {
+            frames.push(block.frame());
         }
+        assert_eq!(frames, vec![0, 1, 2]);
+
+        let mut block = Block::new(BlockKind::Standard, 5);
+        for _ in 0..4 {
+            block.hit();
+        }
+        assert_eq!(block.frame(), DAMAGE_FRAMES - 1);
+
+        let mut block = Block::new(BlockKind::Indestructible, 0);
+        assert!(!block.hit());
+        assert_eq!(block.frame(), INDESTRUCTIBLE_FRAME);
     }
 }
@@ -0,0 +1,27 @@
+use specs::prelude::*;
+
+use crate::collidable::{Collidable, Shape};
+
+/// Marks an entity as something balls bounce off, with the shape they
+/// bounce off of. A ball's own collider is the shape it bounces with, and
+/// power-ups and laser bolts use theirs to find the paddle and blocks.
+#[derive(Copy, Clone, Debug)]
+pub struct Collider {
+    pub shape: Shape,
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Collider {
+    pub fn new(shape: Shape) -> Self {
+        Collider { shape }
+    }
+}
+
+impl Collidable for Collider {
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+}
@@ -1,13 +1,17 @@
 mod ball;
 mod block;
-mod is_collidable;
+mod collider;
 mod paddle;
+mod particle;
+mod power_up;
 mod sprite_render;
 mod transform;
 
-pub use ball::Ball;
-pub use block::Block;
-pub use is_collidable::IsCollidable;
+pub use ball::{Ball, Stuck};
+pub use block::{Block, BlockKind};
+pub use collider::Collider;
 pub use paddle::Paddle;
+pub use particle::Particle;
+pub use power_up::{Bolt, PowerUp, PowerUpKind};
 pub use sprite_render::Sprite;
 pub use transform::Transform;
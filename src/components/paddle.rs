@@ -1,22 +1,52 @@
 use specs::prelude::*;
 
 use crate::asset_manager::AssetManager;
-use crate::components::{IsCollidable, Sprite, Transform};
-use crate::constants::WORLD_WIDTH;
+use crate::collidable::Shape;
+use crate::components::{Collider, Sprite, Transform};
+use crate::constants::{PIXELS_PER_UNIT, WORLD_WIDTH};
+
+/// Width of `paddle.png`, which is stretched to however wide the paddle is.
+const TEXTURE_WIDTH: f32 = 50.0;
 
 #[derive(Default)]
-pub struct Paddle;
+pub struct Paddle {
+    /// Horizontal speed over the last step, in world units per second.
+    pub velocity: f32,
+    /// Width in world units, before any power-up.
+    pub width: f32,
+}
 
 impl Component for Paddle {
-    type Storage = NullStorage<Self>;
+    type Storage = HashMapStorage<Self>;
 }
 
 impl Paddle {
-    pub fn init(world: &mut World) {
+    pub fn new(width: f32) -> Self {
+        Paddle {
+            velocity: 0.0,
+            width,
+        }
+    }
+
+    /// How far the texture is stretched horizontally to be `width` world
+    /// units wide.
+    pub fn stretch(width: f32) -> f32 {
+        width * PIXELS_PER_UNIT / TEXTURE_WIDTH
+    }
+
+    pub fn collider(width: f32) -> Collider {
+        // rounded ends, so the ball glances off the corners
+        Collider::new(Shape::Capsule {
+            half_length: (width / 2.0 - 0.5).max(0.0),
+            radius: 0.5,
+        })
+    }
+
+    pub fn init(world: &mut World, width: f32) {
         let global_t = {
             Transform::default()
                 .with_pos((100., 0.))
-                .with_scale((15., 15.))
+                .with_scale((PIXELS_PER_UNIT, PIXELS_PER_UNIT))
         };
         let tex_info = {
             let mut asset_manager = world.fetch_mut::<AssetManager>();
@@ -25,16 +55,18 @@ impl Paddle {
                 .expect("Failed to load paddle texture")
         };
 
-        let mut s1 = Sprite::new(&tex_info, (0, 0), (50, 15));
+        let mut s1 = Sprite::new(&tex_info, (0, 0), (TEXTURE_WIDTH as u32, 15));
         s1.offsets = [0.5, 0.5];
-        let mut t1 = Transform::default().with_pos((WORLD_WIDTH / 2.0, 1.0));
+        let mut t1 = Transform::default()
+            .with_pos((WORLD_WIDTH / 2.0, 1.0))
+            .with_scale((Paddle::stretch(width), 1.0));
         t1.global = global_t.matrix();
         world
             .create_entity()
             .with(s1)
             .with(t1)
-            .with(Paddle)
-            .with(IsCollidable)
+            .with(Paddle::new(width))
+            .with(Paddle::collider(width))
             .build();
     }
 }
@@ -0,0 +1,13 @@
+use cgmath::Vector2;
+use specs::prelude::*;
+
+/// Purely visual debris, removed once its time is up.
+pub struct Particle {
+    pub velocity: Vector2<f32>,
+    /// Seconds left before it disappears.
+    pub remaining: f32,
+}
+
+impl Component for Particle {
+    type Storage = DenseVecStorage<Self>;
+}
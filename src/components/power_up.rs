@@ -0,0 +1,65 @@
+use specs::prelude::*;
+
+use crate::components::Sprite;
+use crate::constants::POWER_UP_DURATION;
+use crate::types::TextureInfo;
+
+/// Size of one power-up in `power_up.png`, which has one for every kind in
+/// the order of `PowerUpKind::ALL`.
+const FRAME_SIZE: (u32, u32) = (24, 12);
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PowerUpKind {
+    WidePaddle,
+    MultiBall,
+    SlowBall,
+    StickyPaddle,
+    Laser,
+    ExtraLife,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::WidePaddle,
+        PowerUpKind::MultiBall,
+        PowerUpKind::SlowBall,
+        PowerUpKind::StickyPaddle,
+        PowerUpKind::Laser,
+        PowerUpKind::ExtraLife,
+    ];
+
+    /// How long the effect lasts in seconds, or `None` if it happens once
+    /// when the power-up is caught.
+    pub fn duration(self) -> Option<f32> {
+        match self {
+            PowerUpKind::MultiBall | PowerUpKind::ExtraLife => None,
+            _ => Some(POWER_UP_DURATION),
+        }
+    }
+
+    pub fn sprite(self, texture: &TextureInfo) -> Sprite {
+        let (w, h) = FRAME_SIZE;
+        let frame = PowerUpKind::ALL.iter().position(|&k| k == self).unwrap() as u32;
+        let mut sprite = Sprite::new(texture, (frame * w, 0), ((frame + 1) * w, h));
+        sprite.offsets = [0.5, 0.5];
+        sprite
+    }
+}
+
+/// A capsule dropped by a destroyed block, falling until the paddle catches
+/// it or it leaves the bottom of the world.
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+}
+
+impl Component for PowerUp {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A shot fired upwards from the paddle by the laser power-up.
+#[derive(Default)]
+pub struct Bolt;
+
+impl Component for Bolt {
+    type Storage = NullStorage<Self>;
+}
@@ -1,7 +1,6 @@
-use cgmath::{Matrix4, Vector3, Vector4};
+use cgmath::{Matrix4, Vector3};
 use specs::{storage::DenseVecStorage, Component};
 
-use crate::collidable::Collidable;
 use crate::types::{TextureInfo, Vertex, VertexPosition, VertexTextureCoords};
 
 #[derive(Copy, Clone)]
@@ -85,14 +84,3 @@ impl Sprite {
         model * offsets
     }
 }
-
-impl Collidable for Sprite {
-    fn get_hitbox(&self) -> ((f32, f32), (f32, f32)) {
-        let m = self.get_model_matrix();
-
-        let v1 = m * Vector4::new(0., 1., 0., 1.);
-        let v2 = m * Vector4::new(1., 0., 0., 1.);
-
-        ((v1.x, v1.y), (v2.x, v2.y))
-    }
-}
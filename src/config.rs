@@ -0,0 +1,176 @@
+use std::env;
+use std::path::PathBuf;
+
+use crate::high_scores::HighScores;
+
+/// How the game presents its frames.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RenderMode {
+    /// Open a GLFW window and render with luminance.
+    Windowed { width: u32, height: u32 },
+    /// Run the simulation without a window or a GPU context.
+    Headless { width: u32, height: u32 },
+    /// Like `Headless`, but every frame is still drawn, on the CPU by a
+    /// `SoftwareRenderer`.
+    Software { width: u32, height: u32 },
+}
+
+impl RenderMode {
+    pub fn dimensions(&self) -> (u32, u32) {
+        match *self {
+            RenderMode::Windowed { width, height }
+            | RenderMode::Headless { width, height }
+            | RenderMode::Software { width, height } => (width, height),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AppConfig {
+    pub render_mode: RenderMode,
+    /// Stop the app loop after this many frames.
+    pub max_frames: Option<u64>,
+    /// Hold every frame back to this many a second.
+    pub frame_limit: Option<u32>,
+    /// Skip the title and level complete screens instead of waiting for a
+    /// key, and quit on game over. A replay still waits for its own keys,
+    /// and only quits.
+    pub auto_advance: bool,
+    /// Level files to play in order. When empty every level in
+    /// `resources/levels` is played.
+    pub levels: Vec<PathBuf>,
+    /// Start over from the first level after the last one.
+    pub loop_levels: bool,
+    /// Write the input stream to this file.
+    pub record_replay: Option<PathBuf>,
+    /// Drive the game from a previously recorded replay.
+    pub play_replay: Option<PathBuf>,
+    /// Where finished games are entered into the high score table.
+    pub high_scores: Option<PathBuf>,
+    /// The name high scores are entered under.
+    pub player_name: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            render_mode: RenderMode::Windowed {
+                width: 800,
+                height: 600,
+            },
+            max_frames: None,
+            frame_limit: Some(60),
+            auto_advance: false,
+            levels: vec![],
+            loop_levels: false,
+            record_replay: None,
+            play_replay: None,
+            high_scores: HighScores::default_path().ok(),
+            player_name: env::var("USER")
+                .or_else(|_| env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_string()),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn with_render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Runs without a window. Nobody is there to press keys, so this also
+    /// turns on `auto_advance`, and games nobody played aren't entered into
+    /// the high scores unless `with_high_scores` is called afterwards.
+    pub fn headless(self) -> Self {
+        let (width, height) = self.render_mode.dimensions();
+        self.with_render_mode(RenderMode::Headless { width, height })
+            .with_auto_advance(true)
+            .without_high_scores()
+    }
+
+    /// Runs without a window like `headless`, drawing every frame on the CPU.
+    pub fn software(self) -> Self {
+        let (width, height) = self.render_mode.dimensions();
+        self.headless()
+            .with_render_mode(RenderMode::Software { width, height })
+    }
+
+    pub fn with_auto_advance(mut self, auto_advance: bool) -> Self {
+        self.auto_advance = auto_advance;
+        self
+    }
+
+    pub fn with_max_frames(mut self, frames: u64) -> Self {
+        self.max_frames = Some(frames);
+        self
+    }
+
+    pub fn with_frame_limit(mut self, fps: u32) -> Self {
+        self.frame_limit = Some(fps);
+        self
+    }
+
+    /// Runs every frame as soon as the last one is done.
+    pub fn without_frame_limit(mut self) -> Self {
+        self.frame_limit = None;
+        self
+    }
+
+    pub fn with_level<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.levels.push(path.into());
+        self
+    }
+
+    pub fn with_loop_levels(mut self, loop_levels: bool) -> Self {
+        self.loop_levels = loop_levels;
+        self
+    }
+
+    pub fn with_replay_recording<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.record_replay = Some(path.into());
+        self
+    }
+
+    /// Plays back a replay. Its game was already played once, so high scores
+    /// are turned off unless `with_high_scores` is called afterwards.
+    pub fn with_replay_playback<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.play_replay = Some(path.into());
+        self.without_high_scores()
+    }
+
+    pub fn with_high_scores<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.high_scores = Some(path.into());
+        self
+    }
+
+    pub fn without_high_scores(mut self) -> Self {
+        self.high_scores = None;
+        self
+    }
+
+    pub fn with_player_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.player_name = name.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unattended_runs_skip_high_scores() {
+        let config = AppConfig::default().with_high_scores("scores.txt");
+        assert!(config.clone().headless().high_scores.is_none());
+        assert!(config
+            .with_replay_playback("a.replay")
+            .high_scores
+            .is_none());
+
+        let config = AppConfig::default()
+            .headless()
+            .with_high_scores("scores.txt");
+        assert_eq!(config.high_scores, Some(PathBuf::from("scores.txt")));
+    }
+}
@@ -1,5 +1,50 @@
 pub const WORLD_WIDTH: f32 = 40.0;
 pub const WORLD_HEIGHT: f32 = 40.0;
+pub const PIXELS_PER_UNIT: f32 = 15.0;
 
 pub const PADDLE_SPEED: f32 = 20.0;
 pub const BALL_SPEED: f32 = 20.0;
+
+/// Angles in degrees above the horizontal that the ball leaves the paddle
+/// at, when it strikes the very end and the very middle of it.
+pub const PADDLE_MIN_BOUNCE_ANGLE: f32 = 25.0;
+pub const PADDLE_MAX_BOUNCE_ANGLE: f32 = 75.0;
+/// How far along the paddle, in half widths, a strike appears to move when
+/// the paddle is going at full speed.
+pub const PADDLE_SPIN: f32 = 0.3;
+
+pub const STARTING_LIVES: u32 = 3;
+/// How far above the paddle's center a ball waiting to be launched sits.
+pub const BALL_REST_HEIGHT: f32 = 1.0;
+
+/// Size of the squares the world is divided into to find nearby colliders.
+pub const COLLISION_CELL_SIZE: f32 = 2.0;
+
+pub const SIMULATION_RATE: u32 = 60;
+pub const MAX_STEPS_PER_FRAME: u32 = 10;
+
+/// Debris thrown off by every bounce, and how long it lasts in seconds.
+pub const PARTICLES_PER_COLLISION: usize = 4;
+pub const PARTICLE_LIFETIME: f32 = 0.4;
+pub const PARTICLE_SPEED: f32 = 6.0;
+
+/// Chance of a destroyed block dropping a power-up, how fast it falls, and
+/// how many seconds the timed ones last once caught.
+pub const POWER_UP_DROP_CHANCE: f32 = 0.15;
+pub const POWER_UP_FALL_SPEED: f32 = 6.0;
+pub const POWER_UP_DURATION: f32 = 15.0;
+pub const WIDE_PADDLE_FACTOR: f32 = 1.5;
+pub const SLOW_BALL_FACTOR: f32 = 0.6;
+/// Extra balls split off each ball in play by the multi-ball power-up.
+pub const MULTI_BALL_SPLIT: usize = 2;
+pub const LASER_FIRE_INTERVAL: f32 = 0.5;
+pub const LASER_BOLT_SPEED: f32 = 30.0;
+pub const RNG_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+pub const BLOCK_POINTS_PER_HIT: u32 = 10;
+pub const MAX_COMBO_MULTIPLIER: u32 = 8;
+
+pub const LEVEL_COLUMNS: u32 = 20;
+pub const LEVEL_ROWS: u32 = 24;
+pub const LEVEL_DIR: &str = "resources/levels";
+pub const SCREENSHOT_DIR: &str = "screenshots";
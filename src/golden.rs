@@ -0,0 +1,166 @@
+//! Renders levels on the CPU and compares the frames against the images in
+//! `tests/golden`. Run with `UPDATE_GOLDEN=1` to write the current frames
+//! there instead, after a change that is meant to alter what is drawn. When a
+//! frame doesn't match, it is written to `target/golden` along with an image
+//! of the pixels that differ.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::asset_manager::AssetManager;
+use crate::breakout;
+use crate::config::AppConfig;
+use crate::renderer::SoftwareRenderer;
+use crate::resources::{Levels, Time};
+use crate::systems::RenderingSystem;
+use crate::types::GameEvent;
+use crate::Dispatchers;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+/// How far apart two channels of a pixel can be before it counts as changed.
+const CHANNEL_TOLERANCE: u8 = 16;
+/// How many pixels can change before the frame no longer matches.
+const MAX_CHANGED_PIXELS: usize = 64;
+
+/// Plays `frames` frames of `level`, launching the ball straight away, and
+/// returns the last one as drawn by a `RenderingSystem`.
+fn render_level(level: &str, frames: u32) -> RgbaImage {
+    let config = AppConfig::default()
+        .software()
+        .with_level(level)
+        .without_high_scores()
+        .without_frame_limit();
+
+    let mut world = World::new();
+    world.insert(AssetManager::new());
+    let mut reader = {
+        let mut ch = EventChannel::<GameEvent>::new();
+        let reader = ch.register_reader();
+        world.insert(ch);
+        reader
+    };
+    let time = Time::default();
+
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    {
+        let mut dispatchers =
+            Dispatchers::new(&config, None, &time, RenderingSystem::new(&mut renderer)).unwrap();
+        world.insert(time);
+        dispatchers.setup(&mut world);
+        breakout::init(
+            &mut world,
+            Levels::new(config.levels.clone(), false).unwrap(),
+        )
+        .unwrap();
+
+        for _ in 0..frames {
+            dispatchers
+                .update(&mut world, &mut reader, &config)
+                .unwrap();
+            dispatchers.end_frame(&mut world);
+        }
+    }
+
+    RgbaImage::from_raw(WIDTH, HEIGHT, renderer.pixels().to_vec()).unwrap()
+}
+
+/// The number of pixels that changed, and an image of the expected frame
+/// faded to grey with those pixels in red.
+fn compare(actual: &RgbaImage, expected: &RgbaImage) -> (usize, RgbaImage) {
+    let mut changed = 0;
+    let diff = RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let e = expected.get_pixel(x, y);
+        let a = actual.get_pixel(x, y);
+        let differs =
+            e.0.iter()
+                .zip(a.0.iter())
+                .any(|(e, a)| (*e as i16 - *a as i16).abs() > CHANNEL_TOLERANCE as i16);
+        if differs {
+            changed += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let grey = ((e[0] as u16 + e[1] as u16 + e[2] as u16) / 6) as u8;
+            Rgba([grey, grey, grey, 255])
+        }
+    });
+    (changed, diff)
+}
+
+fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+    let golden = Path::new("tests/golden").join(name).with_extension("png");
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden.parent().unwrap()).unwrap();
+        actual.save(&golden).unwrap();
+        return;
+    }
+
+    let expected = image::open(&golden)
+        .unwrap_or_else(|e| panic!("Could not open {}: {}", golden.display(), e))
+        .to_rgba();
+    let out = PathBuf::from("target/golden");
+    if expected.dimensions() != actual.dimensions() {
+        fs::create_dir_all(&out).unwrap();
+        actual
+            .save(out.join(format!("{}.actual.png", name)))
+            .unwrap();
+        panic!(
+            "{} is {:?}, the frame is {:?}",
+            golden.display(),
+            expected.dimensions(),
+            actual.dimensions()
+        );
+    }
+
+    let (changed, diff) = compare(actual, &expected);
+    if changed > MAX_CHANGED_PIXELS {
+        fs::create_dir_all(&out).unwrap();
+        actual
+            .save(out.join(format!("{}.actual.png", name)))
+            .unwrap();
+        diff.save(out.join(format!("{}.diff.png", name))).unwrap();
+        panic!(
+            "{} pixels differ from {}, see {}",
+            changed,
+            golden.display(),
+            out.display()
+        );
+    }
+}
+
+#[test]
+fn golden_level_start() {
+    let frame = render_level("resources/levels/01.txt", 1);
+    assert_matches_golden("classic_start", &frame);
+}
+
+#[test]
+fn golden_level_in_play() {
+    let frame = render_level("resources/levels/01.txt", 150);
+    assert_matches_golden("classic_in_play", &frame);
+}
+
+#[test]
+fn golden_block_kinds() {
+    let frame = render_level("resources/levels/03.txt", 1);
+    assert_matches_golden("staircase_start", &frame);
+}
+
+#[test]
+fn golden_diff_marks_changes() {
+    let expected = RgbaImage::from_pixel(4, 4, Rgba([200, 200, 200, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(1, 2, Rgba([200, 210, 200, 255]));
+    actual.put_pixel(3, 0, Rgba([0, 0, 0, 255]));
+
+    let (changed, diff) = compare(&actual, &expected);
+    assert_eq!(changed, 1);
+    assert_eq!(*diff.get_pixel(3, 0), Rgba([255, 0, 0, 255]));
+    assert_eq!(*diff.get_pixel(1, 2), Rgba([100, 100, 100, 255]));
+}
@@ -0,0 +1,241 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::game_error::GameError;
+
+const HEADER: &str = "breakout-high-scores 1";
+pub const HIGH_SCORE_ENTRIES: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u64,
+    /// 1-based number of the last level played.
+    pub level: usize,
+    /// `YYYY-MM-DD`
+    pub date: String,
+}
+
+/// The best scores, highest first, stored as one tab separated entry per
+/// line: `<score> <level> <date> <name>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScores {
+    entries: Vec<HighScoreEntry>,
+    capacity: usize,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores::new(HIGH_SCORE_ENTRIES)
+    }
+}
+
+impl HighScores {
+    pub fn new(capacity: usize) -> Self {
+        HighScores {
+            entries: vec![],
+            capacity,
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    /// Whether `score` would make it onto the table.
+    pub fn qualifies(&self, score: u64) -> bool {
+        if self.entries.len() < self.capacity {
+            return true;
+        }
+        match self.entries.last() {
+            Some(last) => score > last.score,
+            None => false,
+        }
+    }
+
+    /// Adds an entry and returns its 0-based rank, or `None` if it didn't
+    /// make the table. Ties keep the older entry ahead.
+    pub fn insert(&mut self, mut entry: HighScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        entry.name = entry.name.replace(|c: char| c.is_control(), " ");
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(self.capacity);
+        Some(rank)
+    }
+
+    pub fn parse(source: &str) -> Result<HighScores, GameError> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l))
+            .filter(|(_, l)| !l.trim().is_empty());
+
+        match lines.next() {
+            Some((_, line)) if line.trim() == HEADER => {}
+            _ => {
+                return Err(GameError(format!(
+                    "high scores must start with \"{}\"",
+                    HEADER
+                )))
+            }
+        }
+
+        let mut scores = HighScores::default();
+        for (n, line) in lines {
+            let fields: Vec<&str> = line.splitn(4, '\t').collect();
+            if fields.len() != 4 {
+                return Err(GameError(format!(
+                    "line {}: expected \"<score>\\t<level>\\t<date>\\t<name>\"",
+                    n
+                )));
+            }
+            let score = fields[0]
+                .parse()
+                .map_err(|_| GameError(format!("line {}: invalid score {:?}", n, fields[0])))?;
+            let level = fields[1]
+                .parse()
+                .map_err(|_| GameError(format!("line {}: invalid level {:?}", n, fields[1])))?;
+            scores.insert(HighScoreEntry {
+                name: fields[3].to_string(),
+                score,
+                level,
+                date: fields[2].to_string(),
+            });
+        }
+
+        Ok(scores)
+    }
+
+    /// Loads the table at `path`. A missing file is an empty table.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HighScores, GameError> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(source) => HighScores::parse(&source)
+                .map_err(|e| GameError(format!("invalid high scores {}: {}", path.display(), e))),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(e) => Err(GameError(format!(
+                "failed to read high scores {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GameError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| GameError(format!("failed to create {}: {}", dir.display(), e)))?;
+        }
+        fs::write(path, self.to_string()).map_err(|e| {
+            GameError(format!(
+                "failed to write high scores {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// `high_scores.txt` in the platform's per-user data directory.
+    pub fn default_path() -> Result<PathBuf, GameError> {
+        let var = |name| {
+            env::var_os(name)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        };
+
+        let data_dir = if cfg!(windows) {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support"))
+        } else {
+            var("XDG_DATA_HOME").or_else(|| var("HOME").map(|home| home.join(".local/share")))
+        };
+
+        data_dir
+            .map(|dir| dir.join("breakout-clone").join("high_scores.txt"))
+            .ok_or_else(|| GameError("could not find a user data directory".to_string()))
+    }
+}
+
+impl fmt::Display for HighScores {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for e in &self.entries {
+            writeln!(f, "{}\t{}\t{}\t{}", e.score, e.level, e.date, e.name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::process;
+
+    fn entry(name: &str, score: u64) -> HighScoreEntry {
+        HighScoreEntry {
+            name: name.to_string(),
+            score,
+            level: 1,
+            date: "2020-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn high_scores_sorted_and_truncated() {
+        let mut scores = HighScores::new(2);
+        assert_eq!(scores.insert(entry("a", 10)), Some(0));
+        assert_eq!(scores.insert(entry("b", 30)), Some(0));
+        assert_eq!(scores.insert(entry("c", 10)), None);
+        assert_eq!(scores.insert(entry("d", 20)), Some(1));
+
+        let names: Vec<&str> = scores.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "d"]);
+    }
+
+    #[test]
+    fn high_scores_roundtrip() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("Ada Lovelace", 120));
+        scores.insert(entry("tab\there", 50));
+
+        let parsed = HighScores::parse(&scores.to_string()).unwrap();
+        assert_eq!(parsed, scores);
+        assert_eq!(parsed.entries()[1].name, "tab here");
+    }
+
+    #[test]
+    fn high_scores_invalid() {
+        assert!(HighScores::parse("100\t1\t2020-01-01\tx\n").is_err());
+        assert!(HighScores::parse(&format!("{}\nlots\t1\t2020-01-01\tx\n", HEADER)).is_err());
+    }
+
+    #[test]
+    fn high_scores_save_and_load() {
+        let path = temp_dir()
+            .join(format!("breakout-high-scores-{}", process::id()))
+            .join("scores.txt");
+        assert_eq!(HighScores::load(&path).unwrap(), HighScores::default());
+
+        let mut scores = HighScores::default();
+        scores.insert(entry("a", 10));
+        scores.save(&path).unwrap();
+        assert_eq!(HighScores::load(&path).unwrap(), scores);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
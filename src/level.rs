@@ -0,0 +1,244 @@
+use std::fs;
+use std::path::Path;
+
+use crate::components::BlockKind;
+use crate::constants::{LEVEL_COLUMNS, LEVEL_ROWS, PIXELS_PER_UNIT, WORLD_WIDTH};
+use crate::game_error::GameError;
+
+/// A block as placed by a level file. Rows count down from the top of the
+/// world.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlockSpec {
+    pub column: u32,
+    pub row: u32,
+    pub kind: BlockKind,
+    pub hit_points: u32,
+}
+
+/// A level layout, loaded from a text file:
+///
+/// ```text
+/// # comments start with '#'
+/// name: Classic
+/// paddle_width: 3.33
+/// blocks:
+/// 1111..1111
+/// 22##..##22
+/// ```
+///
+/// `paddle_width` is the width of the paddle in world units, and the ball
+/// starts out resting on it. Every line after `blocks:` is a
+/// row of cells: `.` or a space is empty, `1`-`9` is a block taking that
+/// many hits and `#` is an indestructible block.
+///
+/// Older files gave the ball a starting position as `ball: <x> <y>`. Since
+/// the ball always starts on the paddle, such a line is now an error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub paddle_width: f32,
+    pub blocks: Vec<BlockSpec>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            name: String::new(),
+            paddle_width: 50.0 / PIXELS_PER_UNIT,
+            blocks: vec![],
+        }
+    }
+}
+
+fn parse_cell(c: char) -> Option<Option<(BlockKind, u32)>> {
+    match c {
+        '.' | ' ' => Some(None),
+        '#' => Some(Some((BlockKind::Indestructible, 0))),
+        '1'..='9' => Some(Some((BlockKind::Standard, c.to_digit(10).unwrap()))),
+        _ => None,
+    }
+}
+
+fn parse_f32(n: usize, key: &str, value: &str) -> Result<f32, GameError> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| GameError(format!("line {}: invalid {} {:?}", n, key, value)))
+}
+
+impl Level {
+    pub fn parse(source: &str) -> Result<Level, GameError> {
+        let mut level = Level::default();
+        let mut lines = source.lines().enumerate().map(|(i, l)| (i + 1, l));
+        let mut found_blocks = false;
+
+        for (n, line) in &mut lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, ':');
+            let key = parts.next().unwrap().trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| GameError(format!("line {}: expected \"key: value\"", n)))?
+                .trim();
+
+            match key {
+                "name" => level.name = value.to_string(),
+                "ball" => {
+                    return Err(GameError(format!(
+                        "line {}: ball is no longer supported, the ball starts on the paddle",
+                        n
+                    )));
+                }
+                "paddle_width" => {
+                    let width = parse_f32(n, "paddle_width", value)?;
+                    if width <= 0. || width > WORLD_WIDTH {
+                        return Err(GameError(format!(
+                            "line {}: paddle_width must be between 0 and {}",
+                            n, WORLD_WIDTH
+                        )));
+                    }
+                    level.paddle_width = width;
+                }
+                "blocks" => {
+                    found_blocks = true;
+                    break;
+                }
+                _ => return Err(GameError(format!("line {}: unknown key {:?}", n, key))),
+            }
+        }
+
+        if !found_blocks {
+            return Err(GameError("missing \"blocks:\" section".to_string()));
+        }
+
+        // blank lines are kept as empty rows, only trailing ones are dropped
+        let mut rows: Vec<(usize, &str)> = lines.collect();
+        while let Some((_, r)) = rows.last() {
+            if !r.trim().is_empty() {
+                break;
+            }
+            rows.pop();
+        }
+
+        if rows.len() > LEVEL_ROWS as usize {
+            return Err(GameError(format!(
+                "too many block rows ({}, at most {})",
+                rows.len(),
+                LEVEL_ROWS
+            )));
+        }
+
+        for (row, (n, line)) in rows.iter().enumerate() {
+            let line = line.trim_end();
+            if line.chars().count() > LEVEL_COLUMNS as usize {
+                return Err(GameError(format!(
+                    "line {}: too many columns ({}, at most {})",
+                    n,
+                    line.chars().count(),
+                    LEVEL_COLUMNS
+                )));
+            }
+            for (column, c) in line.chars().enumerate() {
+                let cell = parse_cell(c).ok_or_else(|| {
+                    GameError(format!(
+                        "line {}: unknown cell {:?} in column {}",
+                        n,
+                        c,
+                        column + 1
+                    ))
+                })?;
+                if let Some((kind, hit_points)) = cell {
+                    level.blocks.push(BlockSpec {
+                        column: column as u32,
+                        row: row as u32,
+                        kind,
+                        hit_points,
+                    });
+                }
+            }
+        }
+
+        Ok(level)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Level, GameError> {
+        let path = path.as_ref();
+        println!("Loading level ({})", path.display());
+        let source = fs::read_to_string(path)
+            .map_err(|e| GameError(format!("failed to read level {}: {}", path.display(), e)))?;
+        Level::parse(&source)
+            .map_err(|e| GameError(format!("invalid level {}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_parse() {
+        let level =
+            Level::parse("# test\nname: Test\npaddle_width: 6\nblocks:\n1.#\n\n.3\n").unwrap();
+
+        assert_eq!(level.name, "Test");
+        assert_eq!(level.paddle_width, 6.);
+        assert_eq!(
+            level.blocks,
+            vec![
+                BlockSpec {
+                    column: 0,
+                    row: 0,
+                    kind: BlockKind::Standard,
+                    hit_points: 1
+                },
+                BlockSpec {
+                    column: 2,
+                    row: 0,
+                    kind: BlockKind::Indestructible,
+                    hit_points: 0
+                },
+                BlockSpec {
+                    column: 1,
+                    row: 2,
+                    kind: BlockKind::Standard,
+                    hit_points: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn level_defaults() {
+        let level = Level::parse("blocks:\n1\n").unwrap();
+        assert_eq!(level.paddle_width, Level::default().paddle_width);
+    }
+
+    #[test]
+    fn level_unknown_cell() {
+        let err = Level::parse("blocks:\n11x\n").unwrap_err();
+        assert_eq!(err.0, "line 2: unknown cell 'x' in column 3");
+    }
+
+    #[test]
+    fn level_errors() {
+        assert!(Level::parse("name: no blocks\n").is_err());
+        assert!(Level::parse("colour: red\nblocks:\n").is_err());
+        assert!(Level::parse("ball: 1\nblocks:\n").is_err());
+        assert!(Level::parse("ball: 1 500\nblocks:\n").is_err());
+        let e = Level::parse("ball: 4 5\nblocks:\n").unwrap_err();
+        assert!(e.0.contains("no longer supported"));
+        assert!(Level::parse("paddle_width: wide\nblocks:\n").is_err());
+        assert!(Level::parse(&format!("blocks:\n{}\n", "1".repeat(21))).is_err());
+    }
+
+    #[test]
+    fn level_shipped_files_parse() {
+        let level = Level::parse(include_str!("../resources/levels/01.txt")).unwrap();
+        assert_eq!(level.blocks.len(), 80);
+    }
+}
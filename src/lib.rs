@@ -1,23 +1,301 @@
-use specs::prelude::{DispatcherBuilder, World, WorldExt};
+use specs::prelude::{Dispatcher, DispatcherBuilder, ReaderId, RunNow, System, World, WorldExt};
 use specs::shrev::EventChannel;
 
 mod asset_manager;
 mod breakout;
 mod collidable;
 mod components;
+mod config;
 mod constants;
 mod game_error;
+#[cfg(test)]
+mod golden;
+mod high_scores;
+mod level;
+mod renderer;
+mod replay;
 mod resources;
 mod systems;
 mod types;
 mod util;
 
 use crate::asset_manager::AssetManager;
+pub use crate::config::{AppConfig, RenderMode};
+use crate::constants::LEVEL_DIR;
 pub use crate::game_error::GameError;
-use crate::systems::{BallSystem, FrameLimiterSystem, InputSystem, PaddleSystem, RenderingSystem};
+pub use crate::high_scores::{HighScoreEntry, HighScores};
+pub use crate::renderer::{
+    save_screenshot, Batch, DrawList, DrawVertex, GlRenderer, Renderer, SoftwareRenderer,
+};
+pub use crate::replay::{Replay, ReplayEvent};
+use crate::resources::Levels;
+pub use crate::resources::{GameState, Lives, Score, StateStack, Time};
+use crate::systems::{
+    BallSystem, BlockDamageSystem, CollisionGridSystem, EffectSystem, FrameLimiterSystem,
+    HeadlessRenderingSystem, InputSystem, LaserSystem, LaunchSystem, LevelSystem, LivesSystem,
+    PaddleSystem, ParticleSystem, PowerUpSystem, RenderingSystem, ReplayPlayerSystem,
+    ReplayRecorderSystem, ScoreSystem, StateSystem, TimeSystem,
+};
 pub use crate::types::GameEvent;
 
-pub fn start_app(world: &mut World) -> Result<(), GameError> {
+/// The systems of one frame, split in the order they run: reading time and
+/// input, advancing the game itself one fixed step at a time, and presenting
+/// the result. The game state is updated after the input and after every
+/// step, so the simulation stops on the exact step that left `Playing`.
+/// Rendering carries on while paused.
+struct Dispatchers<'a, 'b> {
+    frame_start: Dispatcher<'a, 'b>,
+    state: StateSystem,
+    simulation: Dispatcher<'a, 'b>,
+    frame_end: Dispatcher<'a, 'b>,
+}
+
+impl<'a, 'b> Dispatchers<'a, 'b> {
+    fn new<R>(
+        config: &AppConfig,
+        replay: Option<Replay>,
+        time: &Time,
+        renderer: R,
+    ) -> Result<Self, GameError>
+    where
+        R: for<'c> RunNow<'c> + 'b,
+    {
+        // Replays only reproduce a session when every frame is exactly one step
+        let time_system = match config.render_mode {
+            RenderMode::Headless { .. } | RenderMode::Software { .. } => TimeSystem::lockstep(),
+            RenderMode::Windowed { .. } if replay.is_some() => TimeSystem::lockstep(),
+            RenderMode::Windowed { .. } => TimeSystem::real(),
+        };
+
+        // A replay already has the key presses that moved it along, skipping
+        // screens or launching for it would take it off its recorded course
+        let auto_advance = config.auto_advance && replay.is_none();
+
+        let mut builder = DispatcherBuilder::new().with(time_system, "time", &[]);
+        let mut input_deps = vec!["time"];
+
+        if let Some(replay) = replay {
+            builder.add(
+                ReplayPlayerSystem::new(replay),
+                "replay playback",
+                &["time"],
+            );
+            input_deps.push("replay playback");
+        }
+        if let Some(path) = &config.record_replay {
+            let recorder = ReplayRecorderSystem::create(path, time.fixed_step())?;
+            builder.add(recorder, "replay recording", &input_deps);
+        }
+
+        let frame_start = builder
+            .with(InputSystem::default(), "input", &input_deps)
+            .build();
+
+        let simulation = DispatcherBuilder::new()
+            .with(PaddleSystem, "paddle movement", &[])
+            .with(LaunchSystem::default(), "ball launch", &["paddle movement"])
+            .with(
+                CollisionGridSystem,
+                "collision grid",
+                &["paddle movement", "ball launch"],
+            )
+            .with(BallSystem, "ball movement", &["collision grid"])
+            .with(LaserSystem::default(), "laser", &["collision grid"])
+            .with(
+                BlockDamageSystem::default(),
+                "block damage",
+                &["ball movement", "laser"],
+            )
+            .with(
+                ParticleSystem::default(),
+                "particles",
+                &["ball movement", "laser"],
+            )
+            .with(PowerUpSystem::default(), "power-ups", &["block damage"])
+            .with(EffectSystem::default(), "effects", &["power-ups"])
+            .with(
+                LivesSystem::default(),
+                "lives",
+                &["ball movement", "paddle movement", "effects"],
+            )
+            .with_barrier()
+            .with(LevelSystem::default(), "level", &[])
+            .with(ScoreSystem::default(), "score", &[])
+            .build();
+
+        let mut builder = DispatcherBuilder::new();
+        if let Some(fps) = config.frame_limit {
+            builder.add(FrameLimiterSystem::new(fps), "fps_limiter", &[]);
+        }
+        let frame_end = builder.with_thread_local(renderer).build();
+
+        Ok(Dispatchers {
+            frame_start,
+            state: StateSystem::new(auto_advance),
+            simulation,
+            frame_end,
+        })
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.frame_start.setup(world);
+        System::setup(&mut self.state, world);
+        self.simulation.setup(world);
+        self.frame_end.setup(world);
+    }
+
+    fn start_frame(&mut self, world: &mut World) {
+        self.frame_start.dispatch(world);
+        self.state.run_now(world);
+    }
+
+    /// Runs a single fixed step, if the clock allows it. Returns whether the
+    /// step was taken.
+    fn step(&mut self, world: &mut World) -> bool {
+        if !world.fetch::<Time>().can_step() {
+            return false;
+        }
+        self.simulation.dispatch(world);
+        world.maintain();
+        world.fetch_mut::<Time>().complete_step();
+        self.state.run_now(world);
+        true
+    }
+
+    /// Everything in a frame up to drawing it: input, then as many steps as
+    /// are due, reacting to the events of each. Returns `true` when the app
+    /// should quit.
+    fn update(
+        &mut self,
+        world: &mut World,
+        reader: &mut ReaderId<GameEvent>,
+        config: &AppConfig,
+    ) -> Result<bool, GameError> {
+        self.start_frame(world);
+        if handle_events(world, reader, config)? {
+            return Ok(true);
+        }
+
+        // Steps left over when the simulation stops part way are dropped
+        let steps = world.fetch::<Time>().steps();
+        for _ in 0..steps {
+            if !self.step(world) {
+                break;
+            }
+            if handle_events(world, reader, config)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn end_frame(&mut self, world: &mut World) {
+        self.frame_end.dispatch(world);
+    }
+}
+
+/// Reacts to the events written since the last call, switching levels and
+/// recording the score as needed. Returns `true` when the app should quit.
+fn handle_events(
+    world: &mut World,
+    reader: &mut ReaderId<GameEvent>,
+    config: &AppConfig,
+) -> Result<bool, GameError> {
+    let mut next_level = false;
+    let mut new_game = false;
+    let mut game_over = false;
+    {
+        let ch = world.fetch::<EventChannel<GameEvent>>();
+        for event in ch.read(reader) {
+            match event {
+                GameEvent::CloseWindow => return Ok(true),
+                GameEvent::LevelComplete(n) => {
+                    println!(
+                        "Level {} complete, score: {}",
+                        n,
+                        world.fetch::<Score>().points
+                    );
+                }
+                GameEvent::AllLevelsComplete => println!("All levels complete"),
+                GameEvent::LifeLost(remaining) => {
+                    println!("Ball lost, {} lives left", remaining);
+                }
+                GameEvent::StateChanged(state) => match state {
+                    GameState::Title => println!("Press Enter to start"),
+                    GameState::Paused => println!("Paused, press N to step"),
+                    GameState::LevelComplete => println!("Press Enter to continue"),
+                    GameState::GameOver => {
+                        println!("Game over, score: {}", world.fetch::<Score>().points);
+                        game_over = true;
+                    }
+                    GameState::Playing => {}
+                },
+                GameEvent::PowerUpCollected(kind) => println!("Power-up: {:?}", kind),
+                GameEvent::PowerUpExpired(kind) => println!("{:?} wore off", kind),
+                GameEvent::NextLevel => next_level = true,
+                GameEvent::NewGame => new_game = true,
+                _ => {}
+            }
+        }
+    }
+    world.maintain();
+
+    if game_over {
+        // A broken table is no reason to end the game it was meant to record
+        if let Some(path) = &config.high_scores {
+            if let Err(e) = breakout::record_high_score(world, path, &config.player_name) {
+                eprintln!("Could not record high score: {}", e);
+            }
+        }
+        if config.auto_advance {
+            return Ok(true);
+        }
+    }
+    if next_level {
+        breakout::next_level(world)?;
+    }
+    if new_game {
+        breakout::new_game(world)?;
+    }
+    Ok(false)
+}
+
+/// Runs the game until it is closed, drawing it the way `config.render_mode`
+/// asks for.
+pub fn start_app(world: &mut World, config: AppConfig) -> Result<(), GameError> {
+    match config.render_mode {
+        RenderMode::Windowed { width, height } => {
+            start_app_with_renderer(world, config, GlRenderer::new(width, height))
+        }
+        RenderMode::Software { width, height } => {
+            start_app_with_renderer(world, config, SoftwareRenderer::new(width, height))
+        }
+        RenderMode::Headless { width, height } => {
+            run_app(world, config, HeadlessRenderingSystem::new(width, height))
+        }
+    }
+}
+
+/// Runs the game until it is closed, drawing it with `renderer`. The render
+/// mode of `config` still decides how the clock runs, but not its size.
+pub fn start_app_with_renderer<R: Renderer>(
+    world: &mut World,
+    config: AppConfig,
+    renderer: R,
+) -> Result<(), GameError> {
+    // Keys pressed while a replay plays would take it off its recorded course
+    let live_input = config.play_replay.is_none();
+    run_app(
+        world,
+        config,
+        RenderingSystem::new(renderer).with_live_input(live_input),
+    )
+}
+
+fn run_app<S>(world: &mut World, config: AppConfig, renderer: S) -> Result<(), GameError>
+where
+    S: for<'c> RunNow<'c>,
+{
     {
         world.insert::<AssetManager>(AssetManager::new());
     };
@@ -30,36 +308,175 @@ pub fn start_app(world: &mut World) -> Result<(), GameError> {
         reader
     };
 
-    let renderer = {
-        let (width, height) = (800, 600);
-        RenderingSystem::new(width, height)
+    let replay = match &config.play_replay {
+        Some(path) => Some(Replay::load(path)?),
+        None => None,
+    };
+    let time = match &replay {
+        Some(replay) => Time::new(replay.fixed_step),
+        None => Time::default(),
     };
 
-    let mut dispatcher = DispatcherBuilder::new()
-        .with(InputSystem::default(), "input", &[])
-        .with(PaddleSystem::default(), "paddle movement", &["input"])
-        .with(BallSystem::default(), "ball movement", &[])
-        .with_barrier()
-        .with(FrameLimiterSystem::new(60), "fps_limiter", &[])
-        .with_thread_local(renderer)
-        .build();
-
-    dispatcher.setup(world);
-
-    breakout::init(world)?;
-
-    'app: loop {
-        dispatcher.dispatch(world);
-        {
-            let ch = world.fetch::<EventChannel<GameEvent>>();
-            for event in ch.read(&mut reader) {
-                if let GameEvent::CloseWindow = event {
-                    break 'app;
-                }
-            }
+    let mut dispatchers = Dispatchers::new(&config, replay, &time, renderer)?;
+
+    world.insert(time);
+    dispatchers.setup(world);
+
+    let levels = if config.levels.is_empty() {
+        Levels::from_dir(LEVEL_DIR, config.loop_levels)?
+    } else {
+        Levels::new(config.levels.clone(), config.loop_levels)?
+    };
+    breakout::init(world, levels)?;
+
+    let mut frames: u64 = 0;
+    loop {
+        if dispatchers.update(world, &mut reader, &config)? {
+            break;
+        }
+        dispatchers.end_frame(world);
+
+        frames += 1;
+        if config.max_frames.is_some_and(|max| frames >= max) {
+            break;
         }
-        world.maintain();
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs;
+    use std::process;
+
+    use cgmath::Vector2;
+    use luminance_glfw::{Action, Key};
+    use specs::prelude::Join;
+
+    use crate::components::{Ball, Block, Transform};
+    use crate::types::InputEvent;
+
+    /// Where the balls are, how many blocks are left and the score.
+    type Outcome = (Vec<Vector2<f32>>, usize, u64);
+
+    /// Plays `frames` frames of the first level, writing `keys` into the
+    /// event channel at the start of the frames they are paired with.
+    fn play(config: &AppConfig, frames: u32, keys: &[(u32, Key, Action)]) -> Outcome {
+        let mut world = World::new();
+        world.insert(AssetManager::new());
+        let mut reader = {
+            let mut ch = EventChannel::<GameEvent>::new();
+            let reader = ch.register_reader();
+            world.insert(ch);
+            reader
+        };
+        let replay = config
+            .play_replay
+            .as_ref()
+            .map(|path| Replay::load(path).unwrap());
+        let time = Time::default();
+
+        let (width, height) = config.render_mode.dimensions();
+        let mut dispatchers = Dispatchers::new(
+            config,
+            replay,
+            &time,
+            HeadlessRenderingSystem::new(width, height),
+        )
+        .unwrap();
+        world.insert(time);
+        dispatchers.setup(&mut world);
+        breakout::init(
+            &mut world,
+            Levels::new(config.levels.clone(), false).unwrap(),
+        )
+        .unwrap();
+
+        for frame in 0..frames {
+            for &(_, key, action) in keys.iter().filter(|(at, _, _)| *at == frame) {
+                world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .single_write(GameEvent::Input(InputEvent::Key(key, action)));
+            }
+            if dispatchers.update(&mut world, &mut reader, config).unwrap() {
+                break;
+            }
+        }
+
+        let balls = (
+            &world.read_storage::<Ball>(),
+            &world.read_storage::<Transform>(),
+        )
+            .join()
+            .map(|(_, t)| t.position)
+            .collect();
+        let blocks = world.read_storage::<Block>().join().count();
+        let points = world.fetch::<Score>().points;
+        (balls, blocks, points)
+    }
+
+    #[test]
+    fn game_over_survives_malformed_high_scores() {
+        let path = temp_dir().join(format!("breakout-bad-scores-{}.txt", process::id()));
+        fs::write(&path, "not a high score table\n").unwrap();
+        let config = AppConfig::default().with_high_scores(&path);
+
+        let mut world = World::new();
+        let mut reader = {
+            let mut ch = EventChannel::<GameEvent>::new();
+            let reader = ch.register_reader();
+            world.insert(ch);
+            reader
+        };
+        world.insert(Score::default());
+        world.insert(Levels::new(vec!["resources/levels/01.txt".into()], false).unwrap());
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(GameEvent::StateChanged(GameState::GameOver));
+
+        let quit = handle_events(&mut world, &mut reader, &config);
+        let table = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(!quit.unwrap());
+        assert_eq!(table, "not a high score table\n");
+    }
+
+    #[test]
+    fn replay_played_back_headless_matches_recording() {
+        let path = temp_dir().join(format!("breakout-replay-{}.replay", process::id()));
+        let level = "resources/levels/01.txt";
+
+        // Played the way a window would be, waiting for every key
+        let recording = AppConfig::default()
+            .headless()
+            .with_auto_advance(false)
+            .with_level(level)
+            .with_replay_recording(&path);
+        let keys = [
+            (10, Key::Enter, Action::Press),
+            (12, Key::Enter, Action::Release),
+            (20, Key::Right, Action::Press),
+            (45, Key::Right, Action::Release),
+            (90, Key::Space, Action::Press),
+            (92, Key::Space, Action::Release),
+            (150, Key::Left, Action::Press),
+            (170, Key::Left, Action::Release),
+        ];
+        let recorded = play(&recording, 400, &keys);
+
+        let playback = AppConfig::default()
+            .headless()
+            .with_level(level)
+            .with_replay_playback(&path);
+        let played = play(&playback, 400, &[]);
+        fs::remove_file(&path).unwrap();
+
+        assert!(recorded.2 > 0);
+        assert_eq!(played, recorded);
+    }
+}
@@ -1,15 +1,86 @@
 #![warn(clippy::pedantic)]
 #![deny(clippy::all)]
 #![allow(clippy::cast_precision_loss)]
+#![allow(clippy::uninlined_format_args)]
 
 use specs::prelude::{World, WorldExt};
 
-use breakout_clone::{start_app, GameError};
+use breakout_clone::{start_app, AppConfig, GameError};
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<AppConfig, GameError> {
+    let mut config = AppConfig::default();
+    // Applied last, so that --headless and --replay don't undo it
+    let mut scores = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => {
+                config = config.headless();
+            }
+            "--software" => {
+                config = config.software();
+            }
+            "--frames" => {
+                let frames = args
+                    .next()
+                    .ok_or_else(|| GameError("--frames requires a value".to_string()))?;
+                let frames = frames
+                    .parse()
+                    .map_err(|_| GameError(format!("invalid frame count: {}", frames)))?;
+                config = config.with_max_frames(frames);
+            }
+            "--level" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| GameError("--level requires a path".to_string()))?;
+                config = config.with_level(path);
+            }
+            "--loop" => {
+                config = config.with_loop_levels(true);
+            }
+            "--scores" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| GameError("--scores requires a path".to_string()))?;
+                scores = Some(path);
+            }
+            "--no-scores" => {
+                scores = None;
+                config = config.without_high_scores();
+            }
+            "--name" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| GameError("--name requires a value".to_string()))?;
+                config = config.with_player_name(name);
+            }
+            "--record" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| GameError("--record requires a path".to_string()))?;
+                config = config.with_replay_recording(path);
+            }
+            "--replay" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| GameError("--replay requires a path".to_string()))?;
+                config = config.with_replay_playback(path);
+            }
+            _ => return Err(GameError(format!("unknown argument: {}", arg))),
+        }
+    }
+
+    if let Some(path) = scores {
+        config = config.with_high_scores(path);
+    }
+    Ok(config)
+}
 
 fn main() -> Result<(), GameError> {
+    let config = parse_args(std::env::args().skip(1))?;
     let mut world = World::new();
 
-    start_app(&mut world)?;
+    start_app(&mut world, config)?;
 
     Ok(())
 }
@@ -0,0 +1,228 @@
+use std::ffi::c_void;
+
+use cgmath::{ortho, Matrix4};
+use luminance::{
+    blending::{Equation, Factor},
+    context::GraphicsContext as _,
+    linear::M44,
+    pipeline::{BoundTexture, PipelineState},
+    pixel::{NormRGBA8UI, NormUnsigned},
+    render_state::RenderState,
+    shader::program::{Program, Uniform},
+    tess::{Mode, Tess, TessBuilder, TessSliceIndex as _},
+    texture::{Dim2, Flat, GenMipmaps, Sampler, Texture},
+};
+use luminance_derive::UniformInterface;
+use luminance_glfw::{Action, GlfwSurface, Key, Surface as _, WindowDim, WindowEvent, WindowOpt};
+
+use crate::renderer::{DrawList, Renderer};
+use crate::types::{
+    GameEvent, InputEvent, Vertex, VertexPosition, VertexSemantics, VertexTextureCoords,
+};
+
+const VS_STR: &str = include_str!("../vs.shader");
+const FS_STR: &str = include_str!("../fs.shader");
+
+#[derive(UniformInterface)]
+struct ShaderInterface {
+    #[uniform(unbound)]
+    world: Uniform<M44>,
+    #[uniform(unbound)]
+    image: Uniform<&'static BoundTexture<'static, Flat, Dim2, NormUnsigned>>,
+}
+
+/// Draws through OpenGL into a GLFW window, which it also reads the
+/// keyboard from.
+pub struct GlRenderer {
+    textures: Vec<Texture<Flat, Dim2, NormRGBA8UI>>,
+    /// Vertex buffer reused from frame to frame, only replaced when the
+    /// sprites no longer fit.
+    tess: Option<Tess>,
+    capacity: usize,
+    world: Matrix4<f32>,
+    program: Program<VertexSemantics, (), ShaderInterface>,
+    surface: GlfwSurface,
+}
+
+impl GlRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let surface = GlfwSurface::new(
+            WindowDim::Windowed(width, height),
+            "No Tilearino",
+            WindowOpt::default(),
+        )
+        .expect("unable to create surface");
+
+        let program = Program::<VertexSemantics, (), ShaderInterface>::from_strings(
+            None, VS_STR, None, FS_STR,
+        )
+        .expect("Could not create shader program");
+
+        if !program.warnings.is_empty() {
+            eprintln!("Warnings: {:?}", program.warnings);
+        }
+
+        GlRenderer {
+            textures: vec![],
+            tess: None,
+            capacity: 0,
+            world: ortho(0., width as f32, 0., height as f32, -1., 1.),
+            program: program.program,
+            surface,
+        }
+    }
+
+    /// Copies the frame's vertices to the GPU, making the buffer bigger
+    /// first if they don't fit.
+    fn upload_vertices(&mut self, list: &DrawList) {
+        let vertices: Vec<Vertex> = list
+            .vertices
+            .iter()
+            .map(|v| Vertex {
+                position: VertexPosition::new(v.position),
+                tex_coords: VertexTextureCoords::new(v.tex_coords),
+            })
+            .collect();
+        if vertices.is_empty() {
+            return;
+        }
+
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            let mut initial = vertices.clone();
+            initial.resize(self.capacity, vertices[0]);
+            self.tess = Some(
+                TessBuilder::new(&mut self.surface)
+                    .add_vertices(initial)
+                    .set_mode(Mode::Triangle)
+                    .build()
+                    .unwrap(),
+            );
+            return;
+        }
+
+        if let Some(tess) = &mut self.tess {
+            let mut slice = tess.as_slice_mut::<Vertex>().unwrap();
+            slice[..vertices.len()].copy_from_slice(&vertices);
+        }
+    }
+
+    /// Clears the window's back buffer and draws the vertices last uploaded
+    /// into it. It is shown on the next swap.
+    fn draw(&mut self, list: &DrawList) {
+        let frame_buffer = self.surface.back_buffer().unwrap();
+        let (program, textures, tess, world) =
+            (&self.program, &self.textures, &self.tess, self.world);
+        let render_state = RenderState::default().set_blending((
+            Equation::Additive,
+            Factor::SrcAlpha,
+            Factor::SrcAlphaComplement,
+        ));
+        self.surface.pipeline_builder().pipeline(
+            &frame_buffer,
+            &PipelineState::default(),
+            |pipeline, mut shading_gate| {
+                let tess = match tess {
+                    Some(tess) => tess,
+                    None => return,
+                };
+                for batch in &list.batches {
+                    let tex = textures.get(batch.texture).unwrap();
+
+                    let bound_tex = pipeline.bind_texture(tex);
+                    shading_gate.shade(program, |iface, mut render_gate| {
+                        iface.world.update(world.into());
+                        iface.image.update(&bound_tex);
+
+                        render_gate.render(&render_state, |mut tess_gate| {
+                            tess_gate.render(tess.slice(batch.start..batch.start + batch.len));
+                        });
+                    });
+                }
+            },
+        );
+    }
+}
+
+impl Renderer for GlRenderer {
+    fn upload_texture(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        let tex = Texture::<Flat, Dim2, NormRGBA8UI>::new(
+            &mut self.surface,
+            [width, height],
+            0,
+            Sampler::default(),
+        )
+        .expect("luminance texture creation");
+
+        tex.upload_raw(GenMipmaps::No, pixels).unwrap();
+        self.textures.push(tex);
+    }
+
+    fn render(&mut self, list: &DrawList) {
+        self.upload_vertices(list);
+        self.draw(list);
+        self.surface.swap_buffers();
+    }
+
+    /// Reads the frame back from the window's back buffer before showing it,
+    /// so it has the size of the framebuffer, which on HiDPI screens is
+    /// bigger than the window.
+    fn read_pixels(&mut self, list: &DrawList) -> Vec<u8> {
+        self.upload_vertices(list);
+        self.draw(list);
+
+        let (width, height) = self.dimensions();
+        let mut texels = vec![0; 4 * (width * height) as usize];
+        // The default framebuffer is still bound from drawing
+        unsafe {
+            gl::ReadBuffer(gl::BACK);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                texels.as_mut_ptr() as *mut c_void,
+            );
+        }
+        self.surface.swap_buffers();
+
+        // OpenGL keeps the bottom row first. Blending mixes the alpha as well,
+        // which would leave the frame see-through under the edges of sprites.
+        for pixel in texels.chunks_mut(4) {
+            pixel[3] = 255;
+        }
+        texels
+            .chunks(4 * width as usize)
+            .rev()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        (self.surface.width(), self.surface.height())
+    }
+
+    fn poll_events(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for event in self.surface.poll_events() {
+            match event {
+                WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+                    events.push(GameEvent::WindowEvent(event));
+                    events.push(GameEvent::CloseWindow);
+                }
+                WindowEvent::FramebufferSize(width, height) => {
+                    self.world = ortho(0., width as f32, 0., height as f32, -1., 1.);
+                }
+                WindowEvent::Key(k, _scancode, action, _mods) => {
+                    events.push(GameEvent::Input(InputEvent::Key(k, action)))
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cgmath::Vector4;
+use image::RgbaImage;
+
+use crate::components::{Sprite, Transform};
+use crate::game_error::GameError;
+use crate::types::{GameEvent, TextureId};
+use crate::util::timestamp;
+
+mod gl;
+mod software;
+
+pub use gl::GlRenderer;
+pub use software::SoftwareRenderer;
+
+/// A corner of a triangle to draw, in screen pixels from the bottom left.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawVertex {
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+}
+
+/// A run of triangles in a `DrawList` that share a texture.
+#[derive(Debug, PartialEq)]
+pub struct Batch {
+    pub texture: TextureId,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Everything to draw in a frame, as triangles in the order they are drawn,
+/// in runs that share a texture. It knows nothing about how it is going to
+/// be drawn.
+#[derive(Debug, Default)]
+pub struct DrawList {
+    pub vertices: Vec<DrawVertex>,
+    pub batches: Vec<Batch>,
+}
+
+impl DrawList {
+    /// Replaces the list with the given sprites, drawn in the order they were
+    /// given in. Neighbours with the same texture share a batch.
+    pub fn rebuild<'s, I>(&mut self, sprites: I)
+    where
+        I: IntoIterator<Item = (&'s Sprite, &'s Transform)>,
+    {
+        self.vertices.clear();
+        self.batches.clear();
+        for (sprite, transform) in sprites {
+            let start = self.vertices.len();
+            self.vertices
+                .extend_from_slice(&sprite_quad(sprite, transform));
+            match self.batches.last_mut() {
+                Some(batch) if batch.texture == sprite.texture.id => batch.len += 6,
+                _ => self.batches.push(Batch {
+                    texture: sprite.texture.id,
+                    start,
+                    len: 6,
+                }),
+            }
+        }
+    }
+}
+
+/// The two triangles of `sprite` drawn at `transform`.
+fn sprite_quad(sprite: &Sprite, transform: &Transform) -> [DrawVertex; 6] {
+    let p = transform.as_screen_point();
+    let m = transform.with_pos((p.x, p.y)).matrix() * sprite.get_model_matrix();
+
+    let corners = sprite.get_vertices();
+    let mut quad = [DrawVertex {
+        position: [0., 0.],
+        tex_coords: [0., 0.],
+    }; 6];
+    for (v, &i) in quad.iter_mut().zip(&[0, 1, 2, 0, 2, 3]) {
+        let corner = corners[i];
+        let position = m * Vector4::new(corner.position[0], corner.position[1], 0., 1.);
+        *v = DrawVertex {
+            position: [position.x, position.y],
+            tex_coords: [corner.tex_coords[0], corner.tex_coords[1]],
+        };
+    }
+    quad
+}
+
+/// Something that can draw a `DrawList`, on a GPU or otherwise.
+pub trait Renderer {
+    /// Takes the next texture from the `AssetManager`, as rows of RGBA pixels
+    /// from the top. Textures are numbered in the order they arrive.
+    fn upload_texture(&mut self, width: u32, height: u32, pixels: &[u8]);
+
+    /// Clears the screen and draws a frame, blending sprites over what is
+    /// below them by their alpha.
+    fn render(&mut self, list: &DrawList);
+
+    /// Size of the screen in pixels.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// Draws a frame the same way as `render`, and also returns it as rows of
+    /// RGBA pixels from the top.
+    fn read_pixels(&mut self, list: &DrawList) -> Vec<u8>;
+
+    /// Window and input events since the last frame, for renderers that have
+    /// a window.
+    fn poll_events(&mut self) -> Vec<GameEvent> {
+        vec![]
+    }
+}
+
+/// Lends a renderer out while its owner keeps it, to read the frames back
+/// afterwards.
+impl<R: Renderer + ?Sized> Renderer for &mut R {
+    fn upload_texture(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        (**self).upload_texture(width, height, pixels)
+    }
+
+    fn render(&mut self, list: &DrawList) {
+        (**self).render(list)
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        (**self).dimensions()
+    }
+
+    fn read_pixels(&mut self, list: &DrawList) -> Vec<u8> {
+        (**self).read_pixels(list)
+    }
+
+    fn poll_events(&mut self) -> Vec<GameEvent> {
+        (**self).poll_events()
+    }
+}
+
+/// Draws `list`, in place of `Renderer::render`, and saves it in `dir` as a
+/// PNG named after the current time.
+/// Returns the path it was saved to.
+pub fn save_screenshot<R: Renderer>(
+    renderer: &mut R,
+    list: &DrawList,
+    dir: &Path,
+) -> Result<PathBuf, GameError> {
+    let (width, height) = renderer.dimensions();
+    let image = RgbaImage::from_raw(width, height, renderer.read_pixels(list))
+        .ok_or_else(|| GameError("frame is smaller than the screen".to_string()))?;
+
+    fs::create_dir_all(dir)
+        .map_err(|e| GameError(format!("failed to create {}: {}", dir.display(), e)))?;
+    // Numbered when there is more than one in the same second
+    let stem = format!("screenshot-{}", timestamp());
+    let mut path = dir.join(format!("{}.png", stem));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}-{}.png", stem, n));
+    }
+
+    image
+        .save(&path)
+        .map_err(|e| GameError(format!("failed to write {}: {}", path.display(), e)))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::process;
+
+    use crate::types::TextureInfo;
+
+    fn round(f: f32) -> f32 {
+        (f * 100.0).round() / 100.0
+    }
+
+    #[test]
+    fn draw_list_batches_neighbours_in_order() {
+        let block = TextureInfo::new(1, 30, 15);
+        let ball = TextureInfo::new(0, 15, 15);
+        let t = Transform::default();
+        let sprites = [
+            Sprite::new(&block, (0, 0), (30, 15)),
+            Sprite::new(&block, (0, 0), (30, 15)),
+            Sprite::new(&ball, (0, 0), (15, 15)),
+            Sprite::new(&block, (0, 0), (30, 15)),
+        ];
+
+        let mut list = DrawList::default();
+        list.rebuild(sprites.iter().map(|s| (s, &t)));
+
+        assert_eq!(list.vertices.len(), 24);
+        assert_eq!(
+            list.batches,
+            vec![
+                Batch {
+                    texture: 1,
+                    start: 0,
+                    len: 12
+                },
+                Batch {
+                    texture: 0,
+                    start: 12,
+                    len: 6
+                },
+                Batch {
+                    texture: 1,
+                    start: 18,
+                    len: 6
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_list_quad_corners() {
+        let tex = TextureInfo::new(0, 100, 50);
+        let mut s = Sprite::new(&tex, (0, 0), (100, 50));
+        s.offsets = [0.5, 0.5];
+        let t = Transform::default().with_pos((200., 100.));
+
+        let quad = sprite_quad(&s, &t);
+        let corner = |i: usize| {
+            let v = quad[i];
+            (
+                round(v.position[0]),
+                round(v.position[1]),
+                v.tex_coords[0],
+                v.tex_coords[1],
+            )
+        };
+
+        // The texture's top left at the top left of the screen rectangle
+        assert_eq!(corner(0), (150.0, 125.0, 0.0, 0.0));
+        assert_eq!(corner(2), (250.0, 75.0, 1.0, 1.0));
+        assert_eq!(corner(3), corner(0));
+        assert_eq!(corner(4), corner(2));
+    }
+
+    #[test]
+    fn screenshots_saved_side_by_side() {
+        let dir = temp_dir().join(format!("breakout-screenshots-{}", process::id()));
+        let mut renderer = SoftwareRenderer::new(4, 3);
+        renderer.upload_texture(1, 1, &[255, 255, 255, 255]);
+        let sprite = Sprite::new(&TextureInfo::new(0, 1, 1), (0, 0), (1, 1));
+        let t = Transform::default().with_pos((1., 2.));
+        let mut list = DrawList::default();
+        list.rebuild(vec![(&sprite, &t)]);
+
+        let first = save_screenshot(&mut renderer, &list, &dir).unwrap();
+        let second = save_screenshot(&mut renderer, &list, &dir).unwrap();
+        assert_ne!(first, second);
+
+        let image = image::open(&first).unwrap().to_rgba();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 255]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+use crate::renderer::{DrawList, DrawVertex, Renderer};
+
+struct SoftwareTexture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl SoftwareTexture {
+    /// The texel at `uv`, without any filtering.
+    fn sample(&self, u: f32, v: f32) -> [u8; 4] {
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v * self.height as f32) as u32).min(self.height - 1);
+        let i = 4 * (y * self.width + x) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+}
+
+/// Draws on the CPU into an RGBA buffer, so that frames can be rendered and
+/// looked at without a GPU or a window.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    textures: Vec<SoftwareTexture>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareRenderer {
+            width,
+            height,
+            pixels: vec![0; (4 * width * height) as usize],
+            textures: vec![],
+        }
+    }
+
+    /// The last frame as rows of RGBA pixels from the top.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The pixel `x` across and `y` down from the top left.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = 4 * (y * self.width + x) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Fills every pixel whose center is inside the triangle. Centers on an
+    /// edge shared by two triangles are only filled by one of them, so that
+    /// see-through sprites aren't drawn twice along their diagonal.
+    fn draw_triangle(&mut self, texture: &SoftwareTexture, tri: &[DrawVertex]) {
+        let edge = |p: [f32; 2], q: [f32; 2], x: f32, y: f32| {
+            (q[0] - p[0]) * (y - p[1]) - (q[1] - p[1]) * (x - p[0])
+        };
+        // Counter-clockwise from here on, so the inside is left of every edge
+        let [p0, p1, p2] = [tri[0].position, tri[1].position, tri[2].position];
+        let tri = if edge(p0, p1, p2[0], p2[1]) < 0.0 {
+            [tri[0], tri[2], tri[1]]
+        } else {
+            [tri[0], tri[1], tri[2]]
+        };
+        let [a, b, c] = [tri[0].position, tri[1].position, tri[2].position];
+        let area = edge(a, b, c[0], c[1]);
+        if area == 0.0 {
+            return;
+        }
+        // Going down or, when level, to the left
+        let top_left = |p: [f32; 2], q: [f32; 2]| q[1] < p[1] || (q[1] == p[1] && q[0] < p[0]);
+        let inside = |w: f32, p, q| w > 0.0 || (w == 0.0 && top_left(p, q));
+
+        let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let max_x = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(self.width);
+        let max_y = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(self.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let (ea, eb, ec) = (edge(b, c, px, py), edge(c, a, px, py), edge(a, b, px, py));
+                if !(inside(ea, b, c) && inside(eb, c, a) && inside(ec, a, b)) {
+                    continue;
+                }
+
+                // Weights of each corner
+                let (wa, wb, wc) = (ea / area, eb / area, ec / area);
+                let u = wa * tri[0].tex_coords[0]
+                    + wb * tri[1].tex_coords[0]
+                    + wc * tri[2].tex_coords[0];
+                let v = wa * tri[0].tex_coords[1]
+                    + wb * tri[1].tex_coords[1]
+                    + wc * tri[2].tex_coords[1];
+                let color = texture.sample(u, v);
+
+                // Screen positions count up from the bottom, rows down from the top
+                let row = self.height - 1 - y;
+                let i = 4 * (row * self.width + x) as usize;
+                blend(&mut self.pixels[i..i + 4], color);
+            }
+        }
+    }
+}
+
+/// Mixes `color` into `pixel` by its alpha, the same way the GPU does. The
+/// screen stays opaque.
+fn blend(pixel: &mut [u8], color: [u8; 4]) {
+    let alpha = color[3] as u32;
+    for (p, c) in pixel[..3].iter_mut().zip(&color[..3]) {
+        *p = ((*c as u32 * alpha + *p as u32 * (255 - alpha) + 127) / 255) as u8;
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn upload_texture(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        self.textures.push(SoftwareTexture {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        });
+    }
+
+    fn render(&mut self, list: &DrawList) {
+        for pixel in self.pixels.chunks_mut(4) {
+            pixel.copy_from_slice(&[0, 0, 0, 255]);
+        }
+
+        // Moved out for the duration, so that the frame can be drawn to
+        let textures = std::mem::take(&mut self.textures);
+        for batch in &list.batches {
+            let texture = &textures[batch.texture];
+            for tri in list.vertices[batch.start..batch.start + batch.len].chunks(3) {
+                self.draw_triangle(texture, tri);
+            }
+        }
+        self.textures = textures;
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn read_pixels(&mut self, list: &DrawList) -> Vec<u8> {
+        self.render(list);
+        self.pixels.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::components::{Sprite, Transform};
+    use crate::types::TextureInfo;
+
+    #[test]
+    fn software_draws_textured_sprite() {
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        // Red on top, blue below
+        let mut texture = vec![];
+        for y in 0..2 {
+            for _ in 0..2 {
+                texture.extend_from_slice(if y == 0 {
+                    &[255, 0, 0, 255]
+                } else {
+                    &[0, 0, 255, 255]
+                });
+            }
+        }
+        renderer.upload_texture(2, 2, &texture);
+
+        let sprite = Sprite::new(&TextureInfo::new(0, 2, 2), (0, 0), (2, 2));
+        // Four times the size, with its top left corner 2 across and 6 up
+        let t = Transform::default().with_pos((2., 6.)).with_scale((2., 2.));
+        let mut list = DrawList::default();
+        list.rebuild(vec![(&sprite, &t)]);
+        renderer.render(&list);
+
+        assert_eq!(renderer.pixel(0, 0), [0, 0, 0, 255]);
+        assert_eq!(renderer.pixel(2, 2), [255, 0, 0, 255]);
+        assert_eq!(renderer.pixel(5, 3), [255, 0, 0, 255]);
+        assert_eq!(renderer.pixel(5, 4), [0, 0, 255, 255]);
+        assert_eq!(renderer.pixel(2, 5), [0, 0, 255, 255]);
+        assert_eq!(renderer.pixel(6, 5), [0, 0, 0, 255]);
+        assert_eq!(renderer.pixel(3, 6), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn software_blends_by_alpha() {
+        let mut renderer = SoftwareRenderer::new(4, 4);
+        renderer.upload_texture(1, 1, &[255, 255, 255, 255]);
+        // Clear in the middle, half see-through at the top left
+        renderer.upload_texture(
+            2,
+            2,
+            &[
+                0, 255, 0, 128, 0, 0, 0, 0, //
+                0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        );
+
+        let white = Sprite::new(&TextureInfo::new(0, 1, 1), (0, 0), (1, 1));
+        let green = Sprite::new(&TextureInfo::new(1, 2, 2), (0, 0), (2, 2));
+        let under = Transform::default().with_pos((0., 4.)).with_scale((4., 4.));
+        let over = Transform::default().with_pos((0., 4.)).with_scale((1., 1.));
+        let mut list = DrawList::default();
+        list.rebuild(vec![(&white, &under), (&green, &over)]);
+        renderer.render(&list);
+
+        assert_eq!(renderer.pixel(0, 0), [127, 255, 127, 255]);
+        assert_eq!(renderer.pixel(1, 0), [255, 255, 255, 255]);
+        assert_eq!(renderer.pixel(1, 1), [255, 255, 255, 255]);
+        assert_eq!(renderer.pixel(3, 3), [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use luminance_glfw::{Action, Key};
+
+use crate::game_error::GameError;
+
+const HEADER: &str = "breakout-replay 1";
+
+/// Keys a replay can carry. Anything else is ignored by the game, so it is
+/// not worth recording.
+const KEYS: &[Key] = &[
+    Key::Left,
+    Key::Right,
+    Key::Up,
+    Key::Down,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Space,
+    Key::Enter,
+    Key::Escape,
+    Key::Tab,
+    Key::Backspace,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+];
+
+fn parse_key(s: &str) -> Option<Key> {
+    KEYS.iter().copied().find(|k| format!("{:?}", k) == s)
+}
+
+fn parse_action(s: &str) -> Option<Action> {
+    match s {
+        "press" => Some(Action::Press),
+        "release" => Some(Action::Release),
+        "repeat" => Some(Action::Repeat),
+        _ => None,
+    }
+}
+
+fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Press => "press",
+        Action::Release => "release",
+        Action::Repeat => "repeat",
+    }
+}
+
+/// A key event and the simulation tick it was applied at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReplayEvent {
+    pub tick: u64,
+    pub key: Key,
+    pub action: Action,
+}
+
+impl ReplayEvent {
+    pub fn is_recordable(key: Key) -> bool {
+        KEYS.contains(&key)
+    }
+}
+
+impl fmt::Display for ReplayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {}",
+            self.tick,
+            self.key,
+            action_name(self.action)
+        )
+    }
+}
+
+/// The input stream of a game session. Played back under the same fixed
+/// step it reproduces the session exactly.
+///
+/// The file format is line based:
+///
+/// ```text
+/// breakout-replay 1
+/// step <fixed step in nanoseconds>
+/// <tick> <key> <press|release|repeat>
+/// ...
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    pub fixed_step: Duration,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    pub fn new(fixed_step: Duration) -> Self {
+        Replay {
+            fixed_step,
+            events: vec![],
+        }
+    }
+
+    pub fn header(fixed_step: Duration) -> String {
+        format!("{}\nstep {}\n", HEADER, fixed_step.as_nanos())
+    }
+
+    pub fn parse(source: &str) -> Result<Replay, GameError> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty());
+
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(GameError(format!("replay must start with \"{}\"", HEADER))),
+        }
+
+        let fixed_step = match lines.next() {
+            Some((n, line)) => {
+                let nanos = line
+                    .strip_prefix("step ")
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+                    .filter(|&nanos| nanos > 0)
+                    .ok_or_else(|| GameError(format!("line {}: expected \"step <nanos>\"", n)))?;
+                Duration::from_nanos(nanos)
+            }
+            None => return Err(GameError("replay is missing its step line".to_string())),
+        };
+
+        let mut replay = Replay::new(fixed_step);
+        for (n, line) in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(GameError(format!(
+                    "line {}: expected \"<tick> <key> <action>\"",
+                    n
+                )));
+            }
+            let tick = fields[0]
+                .parse::<u64>()
+                .map_err(|_| GameError(format!("line {}: invalid tick {:?}", n, fields[0])))?;
+            let key = parse_key(fields[1])
+                .ok_or_else(|| GameError(format!("line {}: unknown key {:?}", n, fields[1])))?;
+            let action = parse_action(fields[2])
+                .ok_or_else(|| GameError(format!("line {}: unknown action {:?}", n, fields[2])))?;
+
+            if let Some(last) = replay.events.last() {
+                if last.tick > tick {
+                    return Err(GameError(format!("line {}: ticks must not decrease", n)));
+                }
+            }
+            replay.events.push(ReplayEvent { tick, key, action });
+        }
+
+        Ok(replay)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, GameError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| GameError(format!("failed to read replay {}: {}", path.display(), e)))?;
+        Replay::parse(&source)
+            .map_err(|e| GameError(format!("invalid replay {}: {}", path.display(), e)))
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Replay::header(self.fixed_step))?;
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_roundtrip() {
+        let mut replay = Replay::new(Duration::from_nanos(16_666_666));
+        replay.events.push(ReplayEvent {
+            tick: 3,
+            key: Key::Left,
+            action: Action::Press,
+        });
+        replay.events.push(ReplayEvent {
+            tick: 40,
+            key: Key::Left,
+            action: Action::Release,
+        });
+
+        let parsed = Replay::parse(&replay.to_string()).unwrap();
+        assert_eq!(parsed, replay);
+    }
+
+    #[test]
+    fn replay_rejects_missing_header() {
+        assert!(Replay::parse("step 10\n0 Left press\n").is_err());
+    }
+
+    #[test]
+    fn replay_rejects_bad_event() {
+        let source = format!(
+            "{}0 Left sideways\n",
+            Replay::header(Duration::from_millis(1))
+        );
+        let err = Replay::parse(&source).unwrap_err();
+        assert_eq!(err.0, "line 3: unknown action \"sideways\"");
+    }
+
+    #[test]
+    fn replay_rejects_decreasing_ticks() {
+        let source = format!(
+            "{}5 Left press\n4 Left release\n",
+            Replay::header(Duration::from_millis(1))
+        );
+        assert!(Replay::parse(&source).is_err());
+    }
+}
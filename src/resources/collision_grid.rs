@@ -0,0 +1,149 @@
+use std::ops::RangeInclusive;
+
+use cgmath::Vector2;
+use specs::Entity;
+
+use crate::collidable::Shape;
+use crate::constants::{COLLISION_CELL_SIZE, WORLD_HEIGHT, WORLD_WIDTH};
+
+/// Something a ball can bounce off, as it was when the grid was built.
+#[derive(Copy, Clone, Debug)]
+pub struct GridEntry {
+    pub entity: Entity,
+    pub shape: Shape,
+    pub position: Vector2<f32>,
+}
+
+/// Uniform grid over the world, used to find the colliders near a ball
+/// without checking every one of them.
+///
+/// Each entry is listed in every cell its bounding box touches. Anything
+/// outside of the world is kept in the nearest cells along the edge.
+#[derive(Debug)]
+pub struct CollisionGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    entries: Vec<GridEntry>,
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        CollisionGrid::new(COLLISION_CELL_SIZE)
+    }
+}
+
+impl CollisionGrid {
+    pub fn new(cell_size: f32) -> Self {
+        let columns = (WORLD_WIDTH / cell_size).ceil().max(1.0) as usize;
+        let rows = (WORLD_HEIGHT / cell_size).ceil().max(1.0) as usize;
+        CollisionGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows],
+            entries: vec![],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.entries.clear();
+    }
+
+    pub fn insert(&mut self, entry: GridEntry) {
+        let index = self.entries.len();
+        let h = entry.shape.half_extents();
+        let p = entry.position;
+        let (columns, rows) = self.cell_range(((p.x - h.x, p.y - h.y), (p.x + h.x, p.y + h.y)));
+        for row in rows {
+            for column in columns.clone() {
+                self.cells[row * self.columns + column].push(index);
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    /// Every entry in the cells touched by `bounds` (lower left and upper
+    /// right corners), each once, in the order they were inserted.
+    pub fn query(&self, bounds: ((f32, f32), (f32, f32))) -> Vec<&GridEntry> {
+        let (columns, rows) = self.cell_range(bounds);
+        let mut found = vec![];
+        for row in rows {
+            for column in columns.clone() {
+                found.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|i| &self.entries[i]).collect()
+    }
+
+    fn cell_range(
+        &self,
+        ((x0, y0), (x1, y1)): ((f32, f32), (f32, f32)),
+    ) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        let cell =
+            |v: f32, count: usize| ((v / self.cell_size).floor().max(0.0) as usize).min(count - 1);
+        (
+            cell(x0, self.columns)..=cell(x1, self.columns),
+            cell(y0, self.rows)..=cell(y1, self.rows),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::prelude::*;
+
+    fn block(entity: Entity, x: f32, y: f32) -> GridEntry {
+        GridEntry {
+            entity,
+            shape: Shape::Aabb {
+                half_width: 1.0,
+                half_height: 0.5,
+            },
+            position: Vector2::new(x, y),
+        }
+    }
+
+    #[test]
+    fn grid_query_nearby_only() {
+        let mut world = World::new();
+        let e: Vec<Entity> = (0..3).map(|_| world.create_entity().build()).collect();
+
+        let mut grid = CollisionGrid::new(2.0);
+        grid.insert(block(e[0], 5.0, 5.0));
+        // straddles four cells
+        grid.insert(block(e[1], 10.0, 10.0));
+        grid.insert(block(e[2], 30.0, 30.0));
+
+        let found: Vec<Entity> = grid
+            .query(((4.0, 4.0), (11.0, 11.0)))
+            .iter()
+            .map(|entry| entry.entity)
+            .collect();
+        assert_eq!(found, vec![e[0], e[1]]);
+
+        assert!(grid.query(((15.0, 15.0), (16.0, 16.0))).is_empty());
+
+        grid.clear();
+        assert!(grid.query(((4.0, 4.0), (11.0, 11.0))).is_empty());
+    }
+
+    #[test]
+    fn grid_outside_world() {
+        let mut world = World::new();
+        let e = world.create_entity().build();
+
+        let mut grid = CollisionGrid::new(2.0);
+        grid.insert(block(e, -5.0, WORLD_HEIGHT + 5.0));
+
+        assert_eq!(grid.query(((-1.0, WORLD_HEIGHT), (0.5, 50.0))).len(), 1);
+    }
+}
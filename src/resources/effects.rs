@@ -0,0 +1,59 @@
+use crate::components::PowerUpKind;
+
+/// Power-ups currently in effect, with the seconds each has left.
+#[derive(Debug, Default)]
+pub struct Effects {
+    active: Vec<(PowerUpKind, f32)>,
+}
+
+impl Effects {
+    /// Starts the effect of `kind`, or restarts its clock if it is already
+    /// running.
+    pub fn start(&mut self, kind: PowerUpKind, duration: f32) {
+        match self.active.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, remaining)) => *remaining = duration,
+            None => self.active.push((kind, duration)),
+        }
+    }
+
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.active.iter().any(|(k, _)| *k == kind)
+    }
+
+    /// Runs the clocks down by `delta_t` and returns the effects that ran out.
+    pub fn tick(&mut self, delta_t: f32) -> Vec<PowerUpKind> {
+        let mut expired = vec![];
+        self.active.retain_mut(|(kind, remaining)| {
+            *remaining -= delta_t;
+            if *remaining > 0.0 {
+                return true;
+            }
+            expired.push(*kind);
+            false
+        });
+        expired
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_expire_and_restart() {
+        let mut effects = Effects::default();
+        effects.start(PowerUpKind::WidePaddle, 1.0);
+        effects.start(PowerUpKind::Laser, 2.0);
+
+        assert!(effects.tick(0.5).is_empty());
+        effects.start(PowerUpKind::WidePaddle, 1.0);
+        assert_eq!(effects.tick(1.0), vec![PowerUpKind::WidePaddle]);
+        assert!(!effects.is_active(PowerUpKind::WidePaddle));
+        assert!(effects.is_active(PowerUpKind::Laser));
+        assert_eq!(effects.tick(1.0), vec![PowerUpKind::Laser]);
+    }
+}
@@ -0,0 +1,74 @@
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameState {
+    Title,
+    Playing,
+    Paused,
+    LevelComplete,
+    GameOver,
+}
+
+impl GameState {
+    /// Whether the gameplay systems (paddle, ball, lives, score...) run.
+    pub fn runs_simulation(self) -> bool {
+        self == GameState::Playing
+    }
+}
+
+/// The active game states. Only the top one is in control; states like
+/// `Paused` sit on top of `Playing` and return to it when popped.
+#[derive(Debug)]
+pub struct StateStack {
+    states: Vec<GameState>,
+}
+
+impl Default for StateStack {
+    fn default() -> Self {
+        StateStack {
+            states: vec![GameState::Title],
+        }
+    }
+}
+
+impl StateStack {
+    pub fn current(&self) -> GameState {
+        *self.states.last().unwrap()
+    }
+
+    pub fn push(&mut self, state: GameState) {
+        self.states.push(state);
+    }
+
+    /// Returns to the state below the current one. The bottom state is never
+    /// popped.
+    pub fn pop(&mut self) -> Option<GameState> {
+        if self.states.len() > 1 {
+            self.states.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the whole stack with `state`.
+    pub fn set(&mut self, state: GameState) {
+        self.states.clear();
+        self.states.push(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_stack_push_pop() {
+        let mut stack = StateStack::default();
+        stack.set(GameState::Playing);
+        stack.push(GameState::Paused);
+        assert_eq!(stack.current(), GameState::Paused);
+
+        assert_eq!(stack.pop(), Some(GameState::Paused));
+        assert_eq!(stack.current(), GameState::Playing);
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.current(), GameState::Playing);
+    }
+}
@@ -0,0 +1,116 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::game_error::GameError;
+
+/// The ordered list of level files being played and the position in it.
+#[derive(Debug)]
+pub struct Levels {
+    paths: Vec<PathBuf>,
+    current: usize,
+    looping: bool,
+}
+
+impl Levels {
+    pub fn new(paths: Vec<PathBuf>, looping: bool) -> Result<Self, GameError> {
+        if paths.is_empty() {
+            return Err(GameError("no levels to play".to_string()));
+        }
+        Ok(Levels {
+            paths,
+            current: 0,
+            looping,
+        })
+    }
+
+    /// Every `.txt` file in `dir`, in file name order.
+    pub fn from_dir<P: AsRef<Path>>(dir: P, looping: bool) -> Result<Self, GameError> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir)
+            .map_err(|e| GameError(format!("failed to list levels in {}: {}", dir.display(), e)))?;
+
+        let mut paths = vec![];
+        for entry in entries {
+            let path = entry
+                .map_err(|e| {
+                    GameError(format!("failed to list levels in {}: {}", dir.display(), e))
+                })?
+                .path();
+            if path.extension() == Some(OsStr::new("txt")) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        Levels::new(paths, looping)
+            .map_err(|_| GameError(format!("no level files in {}", dir.display())))
+    }
+
+    pub fn current_path(&self) -> &Path {
+        &self.paths[self.current]
+    }
+
+    /// 1-based number of the current level.
+    pub fn number(&self) -> usize {
+        self.current + 1
+    }
+
+    /// Goes back to the first level.
+    pub fn restart(&mut self) {
+        self.current = 0;
+    }
+
+    /// Moves on to the next level, wrapping around when looping. Returns
+    /// `false` once the last level is finished and there is nothing left.
+    pub fn advance(&mut self) -> bool {
+        if self.current + 1 < self.paths.len() {
+            self.current += 1;
+            true
+        } else if self.looping {
+            self.current = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]
+    }
+
+    #[test]
+    fn levels_advance_to_end() {
+        let mut levels = Levels::new(paths(), false).unwrap();
+        assert_eq!(levels.number(), 1);
+        assert!(levels.advance());
+        assert_eq!(levels.current_path(), Path::new("b.txt"));
+        assert!(!levels.advance());
+        assert_eq!(levels.number(), 2);
+    }
+
+    #[test]
+    fn levels_loop() {
+        let mut levels = Levels::new(paths(), true).unwrap();
+        assert!(levels.advance());
+        assert!(levels.advance());
+        assert_eq!(levels.number(), 1);
+    }
+
+    #[test]
+    fn levels_empty() {
+        assert!(Levels::new(vec![], false).is_err());
+    }
+
+    #[test]
+    fn levels_shipped_dir() {
+        let levels = Levels::from_dir("resources/levels", false).unwrap();
+        assert_eq!(levels.current_path(), Path::new("resources/levels/01.txt"));
+        assert_eq!(levels.paths.len(), 3);
+    }
+}
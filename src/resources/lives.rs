@@ -0,0 +1,19 @@
+use crate::constants::STARTING_LIVES;
+
+pub struct Lives {
+    pub remaining: u32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Lives {
+            remaining: STARTING_LIVES,
+        }
+    }
+}
+
+impl Lives {
+    pub fn is_game_over(&self) -> bool {
+        self.remaining == 0
+    }
+}
@@ -1,3 +1,19 @@
+mod collision_grid;
+mod effects;
+mod game_state;
 mod input_state;
+mod levels;
+mod lives;
+mod rng;
+mod score;
+mod time;
 
+pub use collision_grid::{CollisionGrid, GridEntry};
+pub use effects::Effects;
+pub use game_state::{GameState, StateStack};
 pub use input_state::InputState;
+pub use levels::Levels;
+pub use lives::Lives;
+pub use rng::Rng;
+pub use score::Score;
+pub use time::Time;
@@ -0,0 +1,53 @@
+use crate::constants::RNG_SEED;
+
+/// A small xorshift generator for gameplay randomness. It is seeded the same
+/// way every run, so that replays play out exactly as they were recorded.
+#[derive(Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::new(RNG_SEED)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift never leaves zero
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 32) as u32
+    }
+
+    /// A number from 0 up to, but not including, 1.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// A number from 0 up to, but not including, `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_f32() * n as f32) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_repeats_from_seed() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let rolls: Vec<usize> = (0..100).map(|_| a.below(6)).collect();
+        assert_eq!(rolls, (0..100).map(|_| b.below(6)).collect::<Vec<_>>());
+        assert!(rolls.iter().all(|&r| r < 6));
+        assert!((0..6).all(|n| rolls.contains(&n)));
+    }
+}
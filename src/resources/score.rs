@@ -0,0 +1,56 @@
+use crate::constants::MAX_COMBO_MULTIPLIER;
+
+/// Points scored so far and the current run of blocks destroyed since the
+/// ball last touched the paddle.
+#[derive(Debug, Default)]
+pub struct Score {
+    pub points: u64,
+    pub combo: u32,
+    pub best_combo: u32,
+}
+
+impl Score {
+    /// The multiplier applied to the next block destroyed.
+    pub fn multiplier(&self) -> u32 {
+        (self.combo + 1).min(MAX_COMBO_MULTIPLIER)
+    }
+
+    pub fn add_block(&mut self, points: u32) {
+        self.points += u64::from(points * self.multiplier());
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+    }
+
+    pub fn reset_combo(&mut self) {
+        self.combo = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_combo_multiplies() {
+        let mut score = Score::default();
+        score.add_block(10);
+        score.add_block(10);
+        score.add_block(20);
+        assert_eq!(score.points, 10 + 20 + 60);
+        assert_eq!(score.combo, 3);
+
+        score.reset_combo();
+        score.add_block(10);
+        assert_eq!(score.points, 100);
+        assert_eq!(score.best_combo, 3);
+    }
+
+    #[test]
+    fn score_multiplier_is_capped() {
+        let mut score = Score::default();
+        for _ in 0..20 {
+            score.add_block(1);
+        }
+        assert_eq!(score.multiplier(), MAX_COMBO_MULTIPLIER);
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use crate::constants::{MAX_STEPS_PER_FRAME, SIMULATION_RATE};
+
+/// The simulation clock shared by every system.
+///
+/// Each frame the elapsed real time is fed into `advance`, which turns it
+/// into a whole number of fixed-size steps. The simulation is then dispatched
+/// `steps()` times, each advancing by `delta_seconds()`, so the outcome only
+/// depends on the number of steps taken and not on how the frames happened to
+/// be scheduled.
+#[derive(Debug)]
+pub struct Time {
+    fixed_step: Duration,
+    accumulator: Duration,
+    time_scale: f32,
+    paused: bool,
+    single_step: bool,
+    steps: u32,
+    tick: u64,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Time::new(Duration::from_secs(1) / SIMULATION_RATE)
+    }
+}
+
+impl Time {
+    pub fn new(fixed_step: Duration) -> Self {
+        Time {
+            fixed_step,
+            accumulator: Duration::from_secs(0),
+            time_scale: 1.0,
+            paused: false,
+            single_step: false,
+            steps: 0,
+            tick: 0,
+        }
+    }
+
+    /// Accumulates `real` elapsed time and works out how many fixed steps the
+    /// simulation takes this frame. Time spent paused is discarded, and if
+    /// the frame fell too far behind the backlog is dropped rather than
+    /// simulated all at once.
+    pub fn advance(&mut self, real: Duration) {
+        self.steps = 0;
+        self.single_step = false;
+
+        if self.paused {
+            self.accumulator = Duration::from_secs(0);
+            return;
+        }
+
+        self.accumulator += real.mul_f64(f64::from(self.time_scale));
+        while self.accumulator >= self.fixed_step {
+            self.accumulator -= self.fixed_step;
+            self.steps += 1;
+
+            if self.steps == MAX_STEPS_PER_FRAME {
+                self.accumulator = Duration::from_secs(0);
+                break;
+            }
+        }
+    }
+
+    /// Number of fixed steps to simulate this frame.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Length of a single step in seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.fixed_step.as_secs_f32()
+    }
+
+    pub fn fixed_step(&self) -> Duration {
+        self.fixed_step
+    }
+
+    /// Lets a paused simulation take exactly one step this frame.
+    pub fn step_once(&mut self) {
+        if self.paused && !self.single_step {
+            self.single_step = true;
+            self.steps = 1;
+        }
+    }
+
+    /// Whether the simulation may take another step: it is running, or it is
+    /// paused and a single step has been asked for.
+    pub fn can_step(&self) -> bool {
+        !self.paused || self.single_step
+    }
+
+    /// Records that one step has been simulated.
+    pub fn complete_step(&mut self) {
+        self.tick += 1;
+        self.single_step = false;
+    }
+
+    /// Number of steps simulated so far.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step() -> Duration {
+        Duration::from_millis(10)
+    }
+
+    #[test]
+    fn time_accumulates_partial_steps() {
+        let mut time = Time::new(step());
+
+        time.advance(Duration::from_millis(6));
+        assert_eq!(time.steps(), 0);
+
+        time.advance(Duration::from_millis(6));
+        assert_eq!(time.steps(), 1);
+
+        time.advance(Duration::from_millis(8));
+        assert_eq!(time.steps(), 1);
+    }
+
+    #[test]
+    fn time_multiple_steps() {
+        let mut time = Time::new(step());
+
+        time.advance(step() * 3);
+        assert_eq!(time.steps(), 3);
+        for _ in 0..time.steps() {
+            time.complete_step();
+        }
+
+        time.advance(Duration::from_secs(0));
+        assert_eq!(time.steps(), 0);
+        assert_eq!(time.tick(), 3);
+    }
+
+    #[test]
+    fn time_paused_discards_elapsed() {
+        let mut time = Time::new(step());
+
+        time.advance(Duration::from_millis(5));
+        time.set_paused(true);
+        time.advance(Duration::from_secs(10));
+        assert_eq!(time.steps(), 0);
+
+        time.set_paused(false);
+        time.advance(Duration::from_millis(5));
+        assert_eq!(time.steps(), 0);
+    }
+
+    #[test]
+    fn time_single_step_while_paused() {
+        let mut time = Time::new(step());
+        time.step_once();
+        assert_eq!(time.steps(), 0);
+
+        time.set_paused(true);
+        time.advance(step());
+        assert!(!time.can_step());
+
+        time.step_once();
+        time.step_once();
+        assert_eq!(time.steps(), 1);
+        assert!(time.can_step());
+
+        time.complete_step();
+        assert!(!time.can_step());
+        assert_eq!(time.tick(), 1);
+    }
+
+    #[test]
+    fn time_scale() {
+        let mut time = Time::new(step());
+        time.set_time_scale(0.5);
+
+        time.advance(step() * 4);
+        assert_eq!(time.steps(), 2);
+    }
+
+    #[test]
+    fn time_caps_steps_per_frame() {
+        let mut time = Time::new(step());
+
+        time.advance(Duration::from_secs(10));
+        assert_eq!(time.steps(), MAX_STEPS_PER_FRAME);
+
+        time.advance(Duration::from_secs(0));
+        assert_eq!(time.steps(), 0);
+    }
+}
@@ -1,128 +1,414 @@
-use std::time::Instant;
-
-use cgmath::Vector2;
+use cgmath::{InnerSpace, Vector2};
 use specs::prelude::*;
+use specs::shrev::EventChannel;
 
-use crate::collidable::Collidable;
-use crate::components::{Ball, Block, IsCollidable, Sprite, Transform};
-use crate::constants::{WORLD_HEIGHT, WORLD_WIDTH};
-use crate::types::OverlapType;
+use crate::collidable::{penetration, sweep, Collidable};
+use crate::components::{Ball, Collider, Paddle, PowerUpKind, Stuck, Transform};
+use crate::constants::{
+    PADDLE_MAX_BOUNCE_ANGLE, PADDLE_MIN_BOUNCE_ANGLE, PADDLE_SPEED, PADDLE_SPIN, WORLD_HEIGHT,
+    WORLD_WIDTH,
+};
+use crate::resources::{CollisionGrid, Effects, Time};
+use crate::types::{Collision, GameEvent};
 
-pub struct BallSystem {
-    last_called: Option<Instant>,
-}
+/// Bounces a ball can make in one step before the rest of its movement is
+/// dropped, so it can't get stuck bouncing between two surfaces forever.
+const MAX_CONTACTS_PER_STEP: usize = 4;
 
-impl Default for BallSystem {
-    fn default() -> Self {
-        BallSystem { last_called: None }
-    }
+/// Where a ball leaving the paddle goes, given how far from the middle of the
+/// paddle it struck: -1 at the left end, 1 at the right. The ball keeps its
+/// speed, and a strike dead in the middle keeps it going the same way sideways.
+pub fn paddle_bounce(velocity: Vector2<f32>, offset: f32) -> Vector2<f32> {
+    let offset = offset.clamp(-1.0, 1.0);
+    let angle = (PADDLE_MAX_BOUNCE_ANGLE
+        + (PADDLE_MIN_BOUNCE_ANGLE - PADDLE_MAX_BOUNCE_ANGLE) * offset.abs())
+    .to_radians();
+    let side = if offset == 0.0 {
+        velocity.x.signum()
+    } else {
+        offset.signum()
+    };
+    Vector2::new(side * angle.cos(), angle.sin()) * velocity.magnitude()
 }
 
+#[derive(Default)]
+pub struct BallSystem;
+
 impl<'a> System<'a> for BallSystem {
     type SystemData = (
         WriteStorage<'a, Ball>,
-        ReadStorage<'a, IsCollidable>,
-        ReadStorage<'a, Block>,
+        ReadStorage<'a, Collider>,
+        Read<'a, CollisionGrid>,
+        ReadStorage<'a, Paddle>,
+        WriteStorage<'a, Stuck>,
+        Read<'a, Effects>,
         WriteStorage<'a, Transform>,
-        ReadStorage<'a, Sprite>,
         Entities<'a>,
+        Read<'a, Time>,
+        Write<'a, EventChannel<GameEvent>>,
+        Write<'a, EventChannel<Collision>>,
     );
     fn run(
         &mut self,
-        (mut balls, collidables, blocks, mut transforms, sprites, entities): Self::SystemData,
+        (
+            mut balls,
+            colliders,
+            grid,
+            paddles,
+            mut stuck,
+            effects,
+            mut transforms,
+            entities,
+            time,
+            mut events,
+            mut collisions,
+        ): Self::SystemData,
     ) {
-        if self.last_called.is_some() {
-            let mut ball_info: Vec<(Entity, bool, bool)> = vec![];
-
-            // Move the ball(s)
-            for (t, mut b, hb, e) in (&mut transforms, &mut balls, &sprites, &entities).join() {
-                let delta_t: f32 =
-                    (Instant::now() - self.last_called.unwrap()).as_millis() as f32 / 1000.0;
-                let dv = delta_t * b.velocity;
-
-                t.set_x(t.position.x + dv.x);
-                t.set_y(t.position.y + dv.y);
-
-                let ((x0, y0), (x1, y1)) = hb.corners(&t);
-                let center = t.as_screen_point();
-                let lower_bounds = t.clone().with_pos((0., 0.)).as_screen_point();
-                let upper_bounds = t
-                    .clone()
-                    .with_pos((WORLD_WIDTH, WORLD_HEIGHT))
-                    .as_screen_point();
-
-                let mut bounce_horiz = false;
-                let mut bounce_vert = false;
-
-                // this only works for symmetric sprites
-                let half_width = (x1 - x0) as f32 / 2.0;
-                let half_height = (y1 - y0) as f32 / 2.0;
-
-                if (center.x - half_width) < lower_bounds.x
-                    || (center.x + half_width) > upper_bounds.x
-                {
-                    bounce_horiz = true;
-                }
+        let delta_t = time.delta_seconds();
+        let sticky = effects.is_active(PowerUpKind::StickyPaddle);
+        let mut caught = vec![];
 
-                if center.y + half_height > upper_bounds.y {
-                    bounce_vert = true;
-                }
+        for (t, b, c, e, _) in (&mut transforms, &mut balls, &colliders, &entities, !&stuck).join()
+        {
+            let shape = c.shape();
 
-                if (center.y - half_height) < lower_bounds.y {
-                    b.velocity = Vector2::new(0., 0.);
-                }
+            // Move the ball up to the first thing in its way, bounce, and
+            // carry on with whatever is left of the step
+            let mut remaining = 1.0;
+            for _ in 0..MAX_CONTACTS_PER_STEP {
+                let dv = delta_t * remaining * b.velocity;
 
-                ball_info.push((e, bounce_horiz, bounce_vert));
-            }
+                // Only look at what is near where the ball is going
+                let ((x0, y0), (x1, y1)) = c.bounds(t);
+                let reach = (
+                    (x0 + dv.x.min(0.0), y0 + dv.y.min(0.0)),
+                    (x1 + dv.x.max(0.0), y1 + dv.y.max(0.0)),
+                );
+                let nearby = grid.query(reach);
 
-            // Check if it bounced off something
-            for (_, t, s, maybe_block, e) in (
-                &collidables,
-                &transforms,
-                &sprites,
-                blocks.maybe(),
-                &entities,
-            )
-                .join()
-            {
-                for (ball_e, bounce_horiz, bounce_vert) in ball_info.iter_mut() {
-                    let ball_sprite = sprites.get(*ball_e).unwrap();
-                    let ball_transform = transforms.get(*ball_e).unwrap();
-                    let mut hit_something = false;
-                    match ball_sprite.intersects(s, &ball_transform, &t) {
-                        OverlapType::None => {}
-                        OverlapType::OnlyX => {
-                            *bounce_horiz = true;
-                            hit_something = true;
-                        }
-                        OverlapType::OnlyY => {
-                            *bounce_vert = true;
-                            hit_something = true;
-                        }
-                        OverlapType::Both => {
-                            *bounce_horiz = true;
-                            *bounce_vert = true;
-                            hit_something = true;
+                // Something moved into the ball (the paddle, most likely), so
+                // push it back out before going anywhere
+                let overlap = nearby
+                    .iter()
+                    .filter_map(|target| {
+                        penetration(&shape, t.position, &target.shape, target.position)
+                            .map(|p| (target, p))
+                    })
+                    .max_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+
+                let (target, normal) = if let Some((target, p)) = overlap {
+                    t.position += p.depth * p.normal;
+                    (target, p.normal)
+                } else {
+                    let hit = nearby
+                        .iter()
+                        .filter_map(|target| {
+                            sweep(&shape, t.position, dv, &target.shape, target.position)
+                                .map(|contact| (target, contact))
+                        })
+                        .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
+
+                    let (target, contact) = match hit {
+                        Some(hit) => hit,
+                        None => {
+                            t.position += dv;
+                            break;
                         }
-                    }
+                    };
+                    t.position += dv * contact.time;
+                    remaining *= 1.0 - contact.time;
+                    (target, contact.normal)
+                };
 
-                    if maybe_block.is_some() && hit_something {
-                        entities.delete(e).unwrap();
+                // Only bounce off things the ball is heading into
+                if b.velocity.dot(normal) >= 0.0 {
+                    continue;
+                }
+                b.velocity -= 2.0 * b.velocity.dot(normal) * normal;
+
+                collisions.single_write(Collision {
+                    a: e,
+                    b: target.entity,
+                    normal,
+                    point: t.position - shape.reach(normal) * normal,
+                });
+
+                // Off the top of the paddle, the player decides where the
+                // ball goes
+                if let Some(paddle) = paddles.get(target.entity) {
+                    if normal.y > 0.0 {
+                        let along = t.position.x - target.position.x;
+                        if sticky {
+                            b.velocity = Vector2::new(0., 0.);
+                            caught.push((e, along));
+                            break;
+                        }
+                        let offset = along / target.shape.half_extents().x
+                            + PADDLE_SPIN * paddle.velocity / PADDLE_SPEED;
+                        b.velocity = paddle_bounce(b.velocity, offset);
                     }
                 }
             }
 
-            for (e, bounce_horiz, bounce_vert) in ball_info.iter() {
-                if *bounce_horiz {
-                    let b = balls.get_mut(*e).unwrap();
-                    b.velocity.x = -b.velocity.x;
-                }
-                if *bounce_vert {
-                    let b = balls.get_mut(*e).unwrap();
-                    b.velocity.y = -b.velocity.y;
-                }
+            let ((x0, y0), (x1, y1)) = c.bounds(t);
+
+            if y0 < 0.0 {
+                b.velocity = Vector2::new(0., 0.);
+                events.single_write(GameEvent::BallLost(e));
+                continue;
+            }
+
+            // Keep the ball inside the walls, and send it back the way it
+            // came if it was on its way out
+            if x0 < 0.0 {
+                t.set_x(t.position.x - x0);
+                b.velocity.x = b.velocity.x.abs();
+            } else if x1 > WORLD_WIDTH {
+                t.set_x(t.position.x - (x1 - WORLD_WIDTH));
+                b.velocity.x = -b.velocity.x.abs();
+            }
+            if y1 > WORLD_HEIGHT {
+                t.set_y(t.position.y - (y1 - WORLD_HEIGHT));
+                b.velocity.y = -b.velocity.y.abs();
             }
         }
-        self.last_called = Some(Instant::now());
+
+        for (e, offset) in caught {
+            stuck.insert(e, Stuck { offset }).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::collidable::Shape;
+    use crate::systems::CollisionGridSystem;
+
+    fn step(world: &World) {
+        CollisionGridSystem.run_now(world);
+        BallSystem.run_now(world);
+    }
+
+    fn ball_world(velocity: Vector2<f32>) -> (World, Entity) {
+        let mut world = World::new();
+        System::setup(&mut BallSystem, &mut world);
+        System::setup(&mut CollisionGridSystem, &mut world);
+
+        let e = world
+            .create_entity()
+            .with(Transform::default().with_pos((WORLD_WIDTH / 2.0, WORLD_HEIGHT / 2.0)))
+            .with(Ball { velocity })
+            .with(Collider::new(Shape::Circle { radius: 0.5 }))
+            .build();
+        (world, e)
+    }
+
+    #[test]
+    fn ball_moves_by_fixed_steps() {
+        let (world, e) = ball_world(Vector2::new(3.0, -6.0));
+        for _ in 0..4 {
+            step(&world);
+        }
+
+        let dt = world.fetch::<Time>().delta_seconds();
+        let t = world.read_storage::<Transform>().get(e).copied().unwrap();
+        assert!((t.position.x - (WORLD_WIDTH / 2.0 + 4.0 * dt * 3.0)).abs() < 1e-4);
+        assert!((t.position.y - (WORLD_HEIGHT / 2.0 - 4.0 * dt * 6.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ball_does_not_tunnel_through_blocks() {
+        // Fast enough to cover 20 units in a single step
+        let (mut world, ball) = ball_world(Vector2::new(0.0, 1200.0));
+        let mut reader = world
+            .fetch_mut::<EventChannel<Collision>>()
+            .register_reader();
+
+        let block = world
+            .create_entity()
+            .with(Transform::default().with_pos((WORLD_WIDTH / 2.0, WORLD_HEIGHT / 2.0 + 10.0)))
+            .with(Collider::new(Shape::Aabb {
+                half_width: 1.0,
+                half_height: 0.5,
+            }))
+            .build();
+
+        step(&world);
+
+        let collisions: Vec<Collision> = world
+            .fetch::<EventChannel<Collision>>()
+            .read(&mut reader)
+            .copied()
+            .collect();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!((collisions[0].a, collisions[0].b), (ball, block));
+        assert_eq!(collisions[0].normal, Vector2::new(0.0, -1.0));
+        assert!((collisions[0].point.y - (WORLD_HEIGHT / 2.0 + 9.5)).abs() < 1e-4);
+
+        let balls = world.read_storage::<Ball>();
+        assert!(balls.get(ball).unwrap().velocity.y < 0.0);
+        let t = world
+            .read_storage::<Transform>()
+            .get(ball)
+            .copied()
+            .unwrap();
+        assert!(t.position.y < WORLD_HEIGHT / 2.0 + 10.0);
+    }
+
+    #[test]
+    fn balls_bounce_off_the_same_block() {
+        let (mut world, first) = ball_world(Vector2::new(0.0, 120.0));
+        let mut reader = world
+            .fetch_mut::<EventChannel<Collision>>()
+            .register_reader();
+
+        let second = world
+            .create_entity()
+            .with(Transform::default().with_pos((WORLD_WIDTH / 2.0 + 0.8, WORLD_HEIGHT / 2.0)))
+            .with(Ball {
+                velocity: Vector2::new(0.0, 120.0),
+            })
+            .with(Collider::new(Shape::Circle { radius: 0.5 }))
+            .build();
+        let block = world
+            .create_entity()
+            .with(
+                Transform::default().with_pos((WORLD_WIDTH / 2.0 + 0.4, WORLD_HEIGHT / 2.0 + 2.0)),
+            )
+            .with(Collider::new(Shape::Aabb {
+                half_width: 1.0,
+                half_height: 0.5,
+            }))
+            .build();
+
+        step(&world);
+
+        let mut hit: Vec<Entity> = world
+            .fetch::<EventChannel<Collision>>()
+            .read(&mut reader)
+            .filter(|collision| collision.b == block)
+            .map(|collision| collision.a)
+            .collect();
+        hit.sort();
+        assert_eq!(hit, vec![first, second]);
+        let balls = world.read_storage::<Ball>();
+        assert!(balls.get(first).unwrap().velocity.y < 0.0);
+        assert!(balls.get(second).unwrap().velocity.y < 0.0);
+    }
+
+    #[test]
+    fn ball_pushed_out_of_paddle_bounces_once() {
+        let (mut world, ball) = ball_world(Vector2::new(0.0, -6.0));
+        let mut reader = world
+            .fetch_mut::<EventChannel<Collision>>()
+            .register_reader();
+
+        // The paddle has moved up into the lower half of the ball
+        let paddle_shape = Shape::Capsule {
+            half_length: 1.0,
+            radius: 0.5,
+        };
+        let paddle = world
+            .create_entity()
+            .with(Transform::default().with_pos((WORLD_WIDTH / 2.0, WORLD_HEIGHT / 2.0 - 0.6)))
+            .with(Paddle::default())
+            .with(Collider::new(paddle_shape))
+            .build();
+
+        for _ in 0..3 {
+            step(&world);
+        }
+
+        let hits = world
+            .fetch::<EventChannel<Collision>>()
+            .read(&mut reader)
+            .filter(|collision| collision.b == paddle)
+            .count();
+        assert_eq!(hits, 1);
+        assert!(world.read_storage::<Ball>().get(ball).unwrap().velocity.y > 0.0);
+
+        let transforms = world.read_storage::<Transform>();
+        let ball_pos = transforms.get(ball).unwrap().position;
+        let paddle_pos = transforms.get(paddle).unwrap().position;
+        let ball_shape = Shape::Circle { radius: 0.5 };
+        assert_eq!(
+            penetration(&ball_shape, ball_pos, &paddle_shape, paddle_pos),
+            None
+        );
+    }
+
+    #[test]
+    fn ball_sticks_to_paddle_when_sticky() {
+        let (mut world, ball) = ball_world(Vector2::new(0.0, -30.0));
+        world
+            .fetch_mut::<Effects>()
+            .start(PowerUpKind::StickyPaddle, 1.0);
+        world
+            .create_entity()
+            .with(
+                Transform::default().with_pos((WORLD_WIDTH / 2.0 - 0.5, WORLD_HEIGHT / 2.0 - 2.0)),
+            )
+            .with(Paddle::default())
+            .with(Collider::new(Shape::Capsule {
+                half_length: 1.0,
+                radius: 0.5,
+            }))
+            .build();
+
+        for _ in 0..3 {
+            step(&world);
+        }
+
+        let stuck = world.read_storage::<Stuck>();
+        assert_eq!(stuck.get(ball).map(|s| s.offset), Some(0.5));
+        assert_eq!(
+            world.read_storage::<Ball>().get(ball).unwrap().velocity,
+            Vector2::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn paddle_bounce_angles() {
+        let incoming = Vector2::new(3.0, -4.0);
+
+        let middle = paddle_bounce(incoming, 0.0);
+        assert!((middle.magnitude() - 5.0).abs() < 1e-4);
+        assert!(middle.x > 0.0);
+
+        let left = paddle_bounce(incoming, -1.0);
+        let angle = left.y.atan2(-left.x).to_degrees();
+        assert!((angle - PADDLE_MIN_BOUNCE_ANGLE).abs() < 1e-3);
+
+        // Never flatter than the minimum, however far off the end
+        let beyond = paddle_bounce(incoming, 3.0);
+        let angle = beyond.y.atan2(beyond.x).to_degrees();
+        assert!((angle - PADDLE_MIN_BOUNCE_ANGLE).abs() < 1e-3);
+
+        let steeper = paddle_bounce(incoming, 0.5);
+        assert!(steeper.y / steeper.x > beyond.y / beyond.x);
+    }
+
+    #[test]
+    fn ball_lost_below_world() {
+        let (world, e) = ball_world(Vector2::new(0.0, -20.0));
+        let mut reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        world
+            .write_storage::<Transform>()
+            .get_mut(e)
+            .unwrap()
+            .set_y(0.6);
+
+        step(&world);
+
+        let lost = world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(&mut reader)
+            .filter(|event| matches!(event, GameEvent::BallLost(lost) if *lost == e))
+            .count();
+        assert_eq!(lost, 1);
     }
 }
@@ -0,0 +1,46 @@
+use specs::prelude::*;
+
+use crate::collidable::Collidable;
+use crate::components::{Ball, Bolt, Collider, PowerUp, Transform};
+use crate::resources::{CollisionGrid, GridEntry};
+
+/// Rebuilds the `CollisionGrid` from everything balls can bounce off. Runs
+/// once things have moved for the step and before the balls do.
+#[derive(Default)]
+pub struct CollisionGridSystem;
+
+impl<'a> System<'a> for CollisionGridSystem {
+    type SystemData = (
+        Write<'a, CollisionGrid>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Ball>,
+        ReadStorage<'a, PowerUp>,
+        ReadStorage<'a, Bolt>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (mut grid, colliders, transforms, balls, power_ups, bolts, entities): Self::SystemData,
+    ) {
+        grid.clear();
+        // Things that move on their own pass through each other
+        for (c, t, e, _, _, _) in (
+            &colliders,
+            &transforms,
+            &entities,
+            !&balls,
+            !&power_ups,
+            !&bolts,
+        )
+            .join()
+        {
+            grid.insert(GridEntry {
+                entity: e,
+                shape: c.shape(),
+                position: t.position,
+            });
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::{Block, Sprite, Transform};
+use crate::types::{Collision, GameEvent};
+
+/// Takes a hit off every block a ball bounces off, shows the damage on its
+/// sprite, and removes the ones that run out of hit points.
+#[derive(Default)]
+pub struct BlockDamageSystem {
+    reader: Option<ReaderId<Collision>>,
+}
+
+impl<'a> System<'a> for BlockDamageSystem {
+    type SystemData = (
+        Read<'a, EventChannel<Collision>>,
+        Write<'a, EventChannel<GameEvent>>,
+        WriteStorage<'a, Block>,
+        WriteStorage<'a, Sprite>,
+        ReadStorage<'a, Transform>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (collisions, mut events, mut blocks, mut sprites, transforms, entities): Self::SystemData,
+    ) {
+        // Deleted blocks stay around until the world is maintained, and
+        // another ball may have bounced off one in the same step
+        let mut destroyed = BitSet::new();
+
+        for collision in collisions.read(self.reader.as_mut().expect("Event reader uninitialized"))
+        {
+            let e = collision.b;
+            if destroyed.contains(e.id()) {
+                continue;
+            }
+            let block = match blocks.get_mut(e) {
+                Some(block) => block,
+                None => continue,
+            };
+            if !block.hit() {
+                if let Some(sprite) = sprites.get_mut(e) {
+                    *sprite = block.sprite(&sprite.texture);
+                }
+                continue;
+            }
+
+            destroyed.add(e.id());
+            entities.delete(e).unwrap();
+            events.single_write(GameEvent::BlockDestroyed {
+                entity: e,
+                position: transforms
+                    .get(e)
+                    .map(|t| t.position)
+                    .unwrap_or(collision.point),
+                kind: block.kind,
+                points: block.points,
+            });
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<Collision>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::Vector2;
+
+    use crate::components::BlockKind;
+
+    #[test]
+    fn block_destroyed_once() {
+        let mut world = World::new();
+        let mut system = BlockDamageSystem::default();
+        System::setup(&mut system, &mut world);
+        let mut reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+
+        let balls: Vec<Entity> = (0..2).map(|_| world.create_entity().build()).collect();
+        let block = world
+            .create_entity()
+            .with(Block::new(BlockKind::Standard, 2))
+            .build();
+
+        let hit = |ball| Collision {
+            a: ball,
+            b: block,
+            normal: Vector2::new(0., -1.),
+            point: Vector2::new(0., 0.),
+        };
+
+        world
+            .fetch_mut::<EventChannel<Collision>>()
+            .single_write(hit(balls[0]));
+        system.run_now(&world);
+        world.maintain();
+        assert!(world.entities().is_alive(block));
+
+        // Both balls strike it in the same step
+        world
+            .fetch_mut::<EventChannel<Collision>>()
+            .iter_write(vec![hit(balls[0]), hit(balls[1])]);
+        system.run_now(&world);
+        world.maintain();
+        assert!(!world.entities().is_alive(block));
+
+        let destroyed = world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(&mut reader)
+            .filter(|event| matches!(event, GameEvent::BlockDestroyed { .. }))
+            .count();
+        assert_eq!(destroyed, 1);
+    }
+}
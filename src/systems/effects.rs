@@ -0,0 +1,203 @@
+use cgmath::{Basis2, Deg, InnerSpace, Rotation, Rotation2, Vector2};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::{Ball, Collider, Paddle, PowerUpKind, Sprite, Transform};
+use crate::constants::{BALL_SPEED, MULTI_BALL_SPLIT, SLOW_BALL_FACTOR, WIDE_PADDLE_FACTOR};
+use crate::resources::{Effects, Lives, Time};
+use crate::types::GameEvent;
+
+/// Starts the effects of caught power-ups and runs their clocks down. The
+/// paddle's width and the balls' speed are worked out again every step from
+/// the effects still running, so nothing is left behind when one runs out.
+/// Losing a life ends every effect.
+#[derive(Default)]
+pub struct EffectSystem {
+    reader: Option<ReaderId<GameEvent>>,
+}
+
+impl<'a> System<'a> for EffectSystem {
+    type SystemData = (
+        Write<'a, EventChannel<GameEvent>>,
+        Write<'a, Effects>,
+        Write<'a, Lives>,
+        ReadStorage<'a, Paddle>,
+        WriteStorage<'a, Ball>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Sprite>,
+        WriteStorage<'a, Collider>,
+        Read<'a, Time>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut events,
+            mut effects,
+            mut lives,
+            paddles,
+            mut balls,
+            mut transforms,
+            mut sprites,
+            mut colliders,
+            time,
+            entities,
+        ): Self::SystemData,
+    ) {
+        let mut collected = vec![];
+        for event in events.read(self.reader.as_mut().expect("Event reader uninitialized")) {
+            match event {
+                GameEvent::PowerUpCollected(kind) => collected.push(*kind),
+                GameEvent::LifeLost(_) => effects.clear(),
+                _ => {}
+            }
+        }
+
+        for kind in collected {
+            if let Some(duration) = kind.duration() {
+                effects.start(kind, duration);
+                continue;
+            }
+            match kind {
+                PowerUpKind::ExtraLife => lives.remaining += 1,
+                PowerUpKind::MultiBall => {
+                    let split: Vec<(Vector2<f32>, Transform, Sprite, Collider)> =
+                        (&balls, &transforms, &sprites, &colliders)
+                            .join()
+                            .map(|(b, t, s, c)| {
+                                // Balls resting on the paddle split off upwards
+                                let velocity = if b.velocity.magnitude2() > 0.0 {
+                                    b.velocity
+                                } else {
+                                    Vector2::new(0., BALL_SPEED)
+                                };
+                                (velocity, *t, *s, *c)
+                            })
+                            .collect();
+                    for (original, t, s, c) in split {
+                        for i in 0..MULTI_BALL_SPLIT {
+                            // Alternately either side of the original
+                            let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                            let angle = Deg(side * 30.0 * (i / 2 + 1) as f32);
+                            let velocity = Basis2::from_angle(angle).rotate_vector(original);
+                            entities
+                                .build_entity()
+                                .with(Ball { velocity }, &mut balls)
+                                .with(t, &mut transforms)
+                                .with(s, &mut sprites)
+                                .with(c, &mut colliders)
+                                .build();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let expired = effects.tick(time.delta_seconds());
+        events.iter_write(expired.into_iter().map(GameEvent::PowerUpExpired));
+
+        let width_factor = if effects.is_active(PowerUpKind::WidePaddle) {
+            WIDE_PADDLE_FACTOR
+        } else {
+            1.0
+        };
+        for (paddle, t, c) in (&paddles, &mut transforms, &mut colliders).join() {
+            let width = paddle.width * width_factor;
+            t.scale.x = Paddle::stretch(width);
+            *c = Paddle::collider(width);
+        }
+
+        let speed = if effects.is_active(PowerUpKind::SlowBall) {
+            BALL_SPEED * SLOW_BALL_FACTOR
+        } else {
+            BALL_SPEED
+        };
+        for b in (&mut balls).join() {
+            if b.velocity.magnitude2() > 0.0 {
+                b.velocity = b.velocity.normalize() * speed;
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::collidable::Collidable;
+
+    #[test]
+    fn effects_apply_and_revert() {
+        let mut world = World::new();
+        let mut system = EffectSystem::default();
+        System::setup(&mut system, &mut world);
+
+        let paddle = world
+            .create_entity()
+            .with(Paddle::new(4.))
+            .with(Transform::default().with_scale((Paddle::stretch(4.), 1.)))
+            .with(Paddle::collider(4.))
+            .build();
+        let ball = world
+            .create_entity()
+            .with(Ball {
+                velocity: Vector2::new(0., BALL_SPEED),
+            })
+            .build();
+
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .iter_write(vec![
+                GameEvent::PowerUpCollected(PowerUpKind::WidePaddle),
+                GameEvent::PowerUpCollected(PowerUpKind::SlowBall),
+                GameEvent::PowerUpCollected(PowerUpKind::ExtraLife),
+            ]);
+        system.run_now(&world);
+
+        let width = |world: &World| {
+            world
+                .read_storage::<Collider>()
+                .get(paddle)
+                .unwrap()
+                .shape()
+                .half_extents()
+                .x
+                * 2.0
+        };
+        let speed = |world: &World| {
+            world
+                .read_storage::<Ball>()
+                .get(ball)
+                .unwrap()
+                .velocity
+                .magnitude()
+        };
+        assert_eq!(width(&world), 4. * WIDE_PADDLE_FACTOR);
+        assert!((speed(&world) - BALL_SPEED * SLOW_BALL_FACTOR).abs() < 1e-4);
+        assert_eq!(
+            world.fetch::<Lives>().remaining,
+            Lives::default().remaining + 1
+        );
+
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(GameEvent::LifeLost(2));
+        system.run_now(&world);
+
+        assert_eq!(width(&world), 4.);
+        let t = *world.read_storage::<Transform>().get(paddle).unwrap();
+        assert_eq!(t.scale.x, Paddle::stretch(4.));
+        assert!((speed(&world) - BALL_SPEED).abs() < 1e-4);
+    }
+}
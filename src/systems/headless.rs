@@ -0,0 +1,39 @@
+use cgmath::{ortho, Matrix4};
+use specs::prelude::*;
+
+use crate::asset_manager::AssetManager;
+use crate::components::Sprite;
+use crate::types::ScreenContext;
+
+/// Stand-in for `RenderingSystem` when there is no display. Textures are
+/// drained from the `AssetManager` and dropped, nothing is drawn. Sprites are
+/// still registered, so that their storage exists without a renderer.
+pub struct HeadlessRenderingSystem {
+    screen_context: ScreenContext,
+}
+
+impl HeadlessRenderingSystem {
+    pub fn new(width: u32, height: u32) -> Self {
+        let world: Matrix4<f32> = ortho(0., width as f32, 0., height as f32, -1., 1.);
+        HeadlessRenderingSystem {
+            screen_context: ScreenContext::new(world, width, height),
+        }
+    }
+}
+
+impl<'a> System<'a> for HeadlessRenderingSystem {
+    type SystemData = WriteExpect<'a, AssetManager>;
+
+    fn run(&mut self, mut asset_manager: Self::SystemData) {
+        asset_manager.upload_textures(|_, _, _| {});
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        world.register::<Sprite>();
+        world
+            .fetch_mut::<AssetManager>()
+            .upload_textures(|_, _, _| {});
+        world.insert(self.screen_context);
+    }
+}
@@ -11,10 +11,11 @@ pub struct InputSystem {
 }
 
 impl<'a> System<'a> for InputSystem {
-    type SystemData = (Read<'a, EventChannel<GameEvent>>, Write<'a, InputState>);
+    type SystemData = (Write<'a, EventChannel<GameEvent>>, Write<'a, InputState>);
 
-    fn run(&mut self, (events, mut state): Self::SystemData) {
+    fn run(&mut self, (mut events, mut state): Self::SystemData) {
         let mut r = self.reader.as_mut().expect("Event reader uninitialized");
+        let mut out = vec![];
         for event in events.read(&mut r) {
             match event {
                 GameEvent::Input(InputEvent::Key(Key::Left, Action::Press))
@@ -49,9 +50,20 @@ impl<'a> System<'a> for InputSystem {
                 | GameEvent::Input(InputEvent::Key(Key::S, Action::Release)) => {
                     state.down = false;
                 }
+                GameEvent::Input(InputEvent::Key(Key::P, Action::Press)) => {
+                    out.push(GameEvent::TogglePause);
+                }
+                GameEvent::Input(InputEvent::Key(Key::N, Action::Press))
+                | GameEvent::Input(InputEvent::Key(Key::N, Action::Repeat)) => {
+                    out.push(GameEvent::StepOnce);
+                }
+                GameEvent::Input(InputEvent::Key(Key::F12, Action::Press)) => {
+                    out.push(GameEvent::Screenshot);
+                }
                 _ => {}
             }
         }
+        events.iter_write(out);
     }
 
     fn setup(&mut self, world: &mut World) {
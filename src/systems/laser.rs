@@ -0,0 +1,194 @@
+use cgmath::Vector2;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::asset_manager::AssetManager;
+use crate::collidable::{sweep, Collidable, Shape};
+use crate::components::{Block, Bolt, Collider, Paddle, PowerUpKind, Sprite, Transform};
+use crate::constants::{LASER_BOLT_SPEED, LASER_FIRE_INTERVAL, WORLD_HEIGHT};
+use crate::resources::{CollisionGrid, Effects, Time};
+use crate::types::{Collision, TextureInfo};
+
+const BOLT_SHAPE: Shape = Shape::Aabb {
+    half_width: 0.1,
+    half_height: 0.3,
+};
+
+/// Fires bolts from both ends of the paddle while the laser power-up is in
+/// effect. A bolt that reaches a block is written to the `Collision` channel,
+/// just like a ball bouncing off it, and disappears.
+#[derive(Default)]
+pub struct LaserSystem {
+    cooldown: f32,
+    texture: Option<TextureInfo>,
+}
+
+impl<'a> System<'a> for LaserSystem {
+    type SystemData = (
+        Read<'a, Effects>,
+        Read<'a, CollisionGrid>,
+        ReadStorage<'a, Paddle>,
+        ReadStorage<'a, Block>,
+        WriteStorage<'a, Bolt>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Sprite>,
+        WriteStorage<'a, Collider>,
+        WriteExpect<'a, AssetManager>,
+        Read<'a, Time>,
+        Entities<'a>,
+        Write<'a, EventChannel<Collision>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            effects,
+            grid,
+            paddles,
+            blocks,
+            mut bolts,
+            mut transforms,
+            mut sprites,
+            mut colliders,
+            mut assets,
+            time,
+            entities,
+            mut collisions,
+        ): Self::SystemData,
+    ) {
+        let delta_t = time.delta_seconds();
+        let dv = Vector2::new(0.0, LASER_BOLT_SPEED * delta_t);
+
+        for (_, t, c, e) in (&bolts, &mut transforms, &colliders, &entities).join() {
+            let ((x0, y0), (x1, y1)) = c.bounds(t);
+            let hit = grid
+                .query(((x0, y0), (x1, y1 + dv.y)))
+                .into_iter()
+                .filter(|target| blocks.contains(target.entity))
+                .filter_map(|target| {
+                    sweep(&c.shape(), t.position, dv, &target.shape, target.position)
+                        .map(|contact| (target, contact))
+                })
+                .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
+
+            if let Some((target, contact)) = hit {
+                t.position += dv * contact.time;
+                collisions.single_write(Collision {
+                    a: e,
+                    b: target.entity,
+                    normal: contact.normal,
+                    point: t.position - c.shape().reach(contact.normal) * contact.normal,
+                });
+                entities.delete(e).unwrap();
+                continue;
+            }
+
+            t.position += dv;
+            if y0 > WORLD_HEIGHT {
+                entities.delete(e).unwrap();
+            }
+        }
+
+        if !effects.is_active(PowerUpKind::Laser) {
+            // Fire straight away the next time it is caught
+            self.cooldown = 0.0;
+            return;
+        }
+        self.cooldown -= delta_t;
+        if self.cooldown > 0.0 {
+            return;
+        }
+        self.cooldown += LASER_FIRE_INTERVAL;
+
+        let guns: Vec<(Vector2<f32>, Transform)> = (&paddles, &transforms, &colliders)
+            .join()
+            .flat_map(|(_, t, c)| {
+                let h = c.shape().half_extents();
+                vec![-1.0, 1.0]
+                    .into_iter()
+                    .map(move |side| (t.position + Vector2::new(side * h.x * 0.8, h.y), *t))
+            })
+            .collect();
+        if guns.is_empty() {
+            return;
+        }
+
+        // The ball texture squeezed into a thin streak
+        let tex = *self.texture.get_or_insert_with(|| {
+            assets
+                .load_texture_image("resources/ball.png")
+                .expect("Failed to load laser texture")
+        });
+        let mut sprite = Sprite::new(&tex, (0, 0), (tex.width, tex.height));
+        sprite.offsets = [0.5, 0.5];
+        let h = BOLT_SHAPE.half_extents();
+        let scale = (2.0 * h.x, 2.0 * h.y);
+
+        for (position, paddle_t) in guns {
+            let mut t = Transform::default().with_pos(position).with_scale(scale);
+            t.global = paddle_t.global;
+            entities
+                .build_entity()
+                .with(t, &mut transforms)
+                .with(sprite, &mut sprites)
+                .with(Collider::new(BOLT_SHAPE), &mut colliders)
+                .with(Bolt, &mut bolts)
+                .build();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::components::BlockKind;
+    use crate::systems::CollisionGridSystem;
+
+    #[test]
+    fn laser_bolts_hit_blocks() {
+        let mut world = World::new();
+        world.insert(AssetManager::new());
+        let mut system = LaserSystem::default();
+        System::setup(&mut system, &mut world);
+        System::setup(&mut CollisionGridSystem, &mut world);
+        let mut reader = world
+            .fetch_mut::<EventChannel<Collision>>()
+            .register_reader();
+
+        world
+            .create_entity()
+            .with(Paddle::new(4.))
+            .with(Transform::default().with_pos((10., 1.)))
+            .with(Paddle::collider(4.))
+            .build();
+        let block = world
+            .create_entity()
+            .with(Block::new(BlockKind::Standard, 1))
+            .with(Transform::default().with_pos((11.5, 20.)))
+            .with(Collider::new(Shape::Aabb {
+                half_width: 1.0,
+                half_height: 0.5,
+            }))
+            .build();
+        world.fetch_mut::<Effects>().start(PowerUpKind::Laser, 10.);
+
+        for _ in 0..60 {
+            CollisionGridSystem.run_now(&world);
+            system.run_now(&world);
+            world.maintain();
+        }
+
+        // Only the bolts from the right end of the paddle line up with it
+        let hits: Vec<Collision> = world
+            .fetch::<EventChannel<Collision>>()
+            .read(&mut reader)
+            .copied()
+            .collect();
+        assert!(!hits.is_empty());
+        assert!(hits
+            .iter()
+            .all(|c| c.b == block && c.normal == Vector2::new(0., -1.)));
+        assert!(hits.iter().all(|c| (c.point.y - 19.5).abs() < 1e-4));
+    }
+}
@@ -0,0 +1,147 @@
+use cgmath::Vector2;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::collidable::Collidable;
+use crate::components::{Ball, Collider, Paddle, Stuck, Transform};
+use crate::constants::{BALL_REST_HEIGHT, BALL_SPEED, PADDLE_SPEED, PADDLE_SPIN};
+use crate::systems::ball::paddle_bounce;
+use crate::types::GameEvent;
+
+/// Carries the balls resting on the paddle along with it, and sends them off
+/// on `Launch`. They leave the paddle the same way a ball bouncing off that
+/// spot would, so where they sit and how the paddle is moving both count.
+#[derive(Default)]
+pub struct LaunchSystem {
+    reader: Option<ReaderId<GameEvent>>,
+}
+
+impl<'a> System<'a> for LaunchSystem {
+    type SystemData = (
+        Read<'a, EventChannel<GameEvent>>,
+        ReadStorage<'a, Paddle>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, Ball>,
+        WriteStorage<'a, Stuck>,
+        WriteStorage<'a, Transform>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (events, paddles, colliders, mut balls, mut stuck, mut transforms, entities): Self::SystemData,
+    ) {
+        let launch = events
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .any(|event| matches!(event, GameEvent::Launch));
+
+        let paddle = (&paddles, &transforms, &colliders)
+            .join()
+            .map(|(p, t, c)| (p.velocity, t.position, c.shape().half_extents().x))
+            .next();
+        let (paddle_velocity, paddle_pos, half_width) = match paddle {
+            Some(paddle) => paddle,
+            None => return,
+        };
+
+        let mut launched = vec![];
+        for (b, s, t, e) in (&mut balls, &stuck, &mut transforms, &entities).join() {
+            t.position = paddle_pos + Vector2::new(s.offset, BALL_REST_HEIGHT);
+            if launch {
+                let offset = s.offset / half_width + PADDLE_SPIN * paddle_velocity / PADDLE_SPEED;
+                b.velocity = paddle_bounce(Vector2::new(0., BALL_SPEED), offset);
+                launched.push(e);
+            }
+        }
+        for e in launched {
+            stuck.remove(e);
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::InnerSpace;
+
+    fn launch_world(paddle_velocity: f32) -> (World, LaunchSystem, Entity, Entity) {
+        let mut world = World::new();
+        let mut system = LaunchSystem::default();
+        System::setup(&mut system, &mut world);
+
+        let paddle = world
+            .create_entity()
+            .with(Paddle {
+                velocity: paddle_velocity,
+                width: 4.,
+            })
+            .with(Transform::default().with_pos((10., 1.)))
+            .with(Paddle::collider(4.))
+            .build();
+        let ball = world
+            .create_entity()
+            .with(Ball {
+                velocity: Vector2::new(0., 0.),
+            })
+            .with(Stuck { offset: 1. })
+            .with(Transform::default())
+            .build();
+        (world, system, paddle, ball)
+    }
+
+    #[test]
+    fn ball_follows_paddle_until_launched() {
+        let (world, mut system, paddle, ball) = launch_world(0.);
+
+        world
+            .write_storage::<Transform>()
+            .get_mut(paddle)
+            .unwrap()
+            .set_x(15.);
+        system.run_now(&world);
+        let t = *world.read_storage::<Transform>().get(ball).unwrap();
+        assert_eq!(t.position, Vector2::new(16., 1. + BALL_REST_HEIGHT));
+        assert_eq!(
+            world.read_storage::<Ball>().get(ball).unwrap().velocity,
+            Vector2::new(0., 0.)
+        );
+
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(GameEvent::Launch);
+        system.run_now(&world);
+        assert!(!world.read_storage::<Stuck>().contains(ball));
+        let velocity = world.read_storage::<Ball>().get(ball).unwrap().velocity;
+        assert!((velocity.magnitude() - BALL_SPEED).abs() < 1e-4);
+        assert!(velocity.x > 0. && velocity.y > 0.);
+    }
+
+    #[test]
+    fn launch_follows_paddle_movement() {
+        let launch_angle = |paddle_velocity| {
+            let (world, mut system, _, _) = launch_world(paddle_velocity);
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .single_write(GameEvent::Launch);
+            system.run_now(&world);
+            let v = world.read_storage::<Ball>().join().next().unwrap().velocity;
+            v.y.atan2(v.x)
+        };
+
+        // Moving left pulls the ball back towards the middle and straightens
+        // it up, moving right sends it off flatter
+        let still = launch_angle(0.);
+        assert!(launch_angle(-PADDLE_SPEED) > still);
+        assert!(launch_angle(PADDLE_SPEED) < still);
+    }
+}
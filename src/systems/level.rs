@@ -0,0 +1,77 @@
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::Block;
+use crate::resources::Levels;
+use crate::types::GameEvent;
+
+/// Announces the end of a level once no destructible block is left.
+#[derive(Default)]
+pub struct LevelSystem {
+    completed: bool,
+}
+
+impl<'a> System<'a> for LevelSystem {
+    type SystemData = (
+        ReadStorage<'a, Block>,
+        ReadExpect<'a, Levels>,
+        Write<'a, EventChannel<GameEvent>>,
+    );
+
+    fn run(&mut self, (blocks, levels, mut events): Self::SystemData) {
+        if blocks.join().any(Block::is_destructible) {
+            self.completed = false;
+        } else if !self.completed {
+            self.completed = true;
+            events.single_write(GameEvent::LevelComplete(levels.number()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::components::BlockKind;
+
+    fn completions(world: &World, reader: &mut ReaderId<GameEvent>) -> Vec<usize> {
+        world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(reader)
+            .filter_map(|e| match e {
+                GameEvent::LevelComplete(n) => Some(*n),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn level_complete_once_destructible_blocks_are_gone() {
+        let mut world = World::new();
+        let mut system = LevelSystem::default();
+        System::setup(&mut system, &mut world);
+        world.insert(Levels::new(vec![PathBuf::from("a.txt")], false).unwrap());
+        let mut reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+
+        let block = world
+            .create_entity()
+            .with(Block::new(BlockKind::Standard, 1))
+            .build();
+        world
+            .create_entity()
+            .with(Block::new(BlockKind::Indestructible, 0))
+            .build();
+
+        system.run_now(&world);
+        assert!(completions(&world, &mut reader).is_empty());
+
+        world.delete_entity(block).unwrap();
+        system.run_now(&world);
+        system.run_now(&world);
+        assert_eq!(completions(&world, &mut reader), vec![1]);
+    }
+}
@@ -0,0 +1,199 @@
+use cgmath::Vector2;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::{Ball, Paddle, Stuck, Transform};
+use crate::constants::BALL_REST_HEIGHT;
+use crate::resources::Lives;
+use crate::types::GameEvent;
+
+/// Removes lost balls while others are still in play. Losing the last one
+/// takes a life and puts the ball back on the paddle to be launched again,
+/// or ends the game once no lives are left.
+#[derive(Default)]
+pub struct LivesSystem {
+    reader: Option<ReaderId<GameEvent>>,
+}
+
+impl<'a> System<'a> for LivesSystem {
+    type SystemData = (
+        Write<'a, EventChannel<GameEvent>>,
+        Write<'a, Lives>,
+        ReadStorage<'a, Paddle>,
+        WriteStorage<'a, Ball>,
+        WriteStorage<'a, Stuck>,
+        WriteStorage<'a, Transform>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (mut events, mut lives, paddles, mut balls, mut stuck, mut transforms, entities): Self::SystemData,
+    ) {
+        let lost: Vec<Entity> = events
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .filter_map(|event| match event {
+                GameEvent::BallLost(e) => Some(*e),
+                _ => None,
+            })
+            .collect();
+
+        if lost.is_empty() {
+            return;
+        }
+
+        // Keep one of the balls for the next life if none are left in play
+        let in_play = (&balls, &entities)
+            .join()
+            .filter(|(_, e)| !lost.contains(e))
+            .count();
+        let (last, extra) = if in_play == 0 {
+            (Some(lost[0]), &lost[1..])
+        } else {
+            (None, &lost[..])
+        };
+        for e in extra {
+            entities.delete(*e).unwrap();
+        }
+
+        let e = match last {
+            Some(e) if !lives.is_game_over() => e,
+            _ => return,
+        };
+
+        lives.remaining -= 1;
+        events.single_write(GameEvent::LifeLost(lives.remaining));
+
+        if lives.is_game_over() {
+            entities.delete(e).unwrap();
+            events.single_write(GameEvent::GameOver);
+            return;
+        }
+
+        let paddle_pos = (&paddles, &transforms)
+            .join()
+            .map(|(_, t)| t.position)
+            .next();
+        if let (Some(p), Some(t)) = (paddle_pos, transforms.get_mut(e)) {
+            t.position = p + Vector2::new(0., BALL_REST_HEIGHT);
+        }
+        if let Some(b) = balls.get_mut(e) {
+            b.velocity = Vector2::new(0., 0.);
+        }
+        stuck.insert(e, Stuck { offset: 0. }).unwrap();
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lives_world() -> (World, LivesSystem, Entity) {
+        let mut world = World::new();
+        let mut system = LivesSystem::default();
+        System::setup(&mut system, &mut world);
+
+        world
+            .create_entity()
+            .with(Paddle::default())
+            .with(Transform::default().with_pos((10., 1.)))
+            .build();
+        let ball = world
+            .create_entity()
+            .with(Ball {
+                velocity: Vector2::new(0., 0.),
+            })
+            .with(Transform::default().with_pos((3., -1.)))
+            .build();
+
+        (world, system, ball)
+    }
+
+    fn lose_ball(world: &World, system: &mut LivesSystem, ball: Entity) {
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(GameEvent::BallLost(ball));
+        system.run_now(world);
+    }
+
+    #[test]
+    fn lost_ball_respawns_on_paddle() {
+        let (world, mut system, ball) = lives_world();
+
+        lose_ball(&world, &mut system, ball);
+
+        assert_eq!(world.fetch::<Lives>().remaining, 2);
+        let t = *world.read_storage::<Transform>().get(ball).unwrap();
+        assert_eq!(t.position, Vector2::new(10., 1. + BALL_REST_HEIGHT));
+        let b = world.read_storage::<Ball>();
+        assert_eq!(b.get(ball).unwrap().velocity, Vector2::new(0., 0.));
+        assert!(world.read_storage::<Stuck>().contains(ball));
+    }
+
+    #[test]
+    fn last_life_ends_game() {
+        let (mut world, mut system, ball) = lives_world();
+        let mut reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+
+        for _ in 0..3 {
+            lose_ball(&world, &mut system, ball);
+        }
+
+        world.maintain();
+        assert!(world.fetch::<Lives>().is_game_over());
+        assert!(!world.is_alive(ball));
+        let game_over = world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(&mut reader)
+            .filter(|e| matches!(e, GameEvent::GameOver))
+            .count();
+        assert_eq!(game_over, 1);
+    }
+
+    #[test]
+    fn life_lost_only_with_last_ball() {
+        let (mut world, mut system, ball) = lives_world();
+        let extra: Vec<Entity> = (0..2)
+            .map(|_| {
+                world
+                    .create_entity()
+                    .with(Ball {
+                        velocity: Vector2::new(0., 0.),
+                    })
+                    .with(Transform::default().with_pos((5., -1.)))
+                    .build()
+            })
+            .collect();
+
+        lose_ball(&world, &mut system, extra[0]);
+        world.maintain();
+        assert_eq!(world.fetch::<Lives>().remaining, 3);
+        assert!(!world.is_alive(extra[0]));
+
+        // The last two drop in the same step
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .iter_write(vec![
+                GameEvent::BallLost(ball),
+                GameEvent::BallLost(extra[1]),
+            ]);
+        system.run_now(&world);
+        world.maintain();
+
+        assert_eq!(world.fetch::<Lives>().remaining, 2);
+        assert!(world.is_alive(ball));
+        assert!(!world.is_alive(extra[1]));
+        assert_eq!(world.read_storage::<Ball>().count(), 1);
+    }
+}
@@ -1,11 +1,39 @@
 mod ball;
+mod collision_grid;
+mod damage;
+mod effects;
+mod headless;
 mod input;
+mod laser;
+mod launch;
+mod level;
 mod limiter;
+mod lives;
 mod paddle;
+mod particles;
+mod power_up;
 mod render;
+mod replay;
+mod score;
+mod state;
+mod time;
 
 pub use ball::BallSystem;
+pub use collision_grid::CollisionGridSystem;
+pub use damage::BlockDamageSystem;
+pub use effects::EffectSystem;
+pub use headless::HeadlessRenderingSystem;
 pub use input::InputSystem;
+pub use laser::LaserSystem;
+pub use launch::LaunchSystem;
+pub use level::LevelSystem;
 pub use limiter::FrameLimiterSystem;
+pub use lives::LivesSystem;
 pub use paddle::PaddleSystem;
+pub use particles::ParticleSystem;
+pub use power_up::PowerUpSystem;
 pub use render::RenderingSystem;
+pub use replay::{ReplayPlayerSystem, ReplayRecorderSystem};
+pub use score::ScoreSystem;
+pub use state::StateSystem;
+pub use time::TimeSystem;
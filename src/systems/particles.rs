@@ -0,0 +1,150 @@
+use cgmath::{Basis2, Deg, Rotation, Rotation2};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::asset_manager::AssetManager;
+use crate::components::{Particle, Sprite, Transform};
+use crate::constants::{PARTICLES_PER_COLLISION, PARTICLE_LIFETIME, PARTICLE_SPEED};
+use crate::resources::Time;
+use crate::types::{Collision, TextureInfo};
+
+/// Size of a particle, relative to the ball it is drawn with.
+const PARTICLE_SCALE: f32 = 0.3;
+
+/// Throws a little debris off wherever a ball bounces, and moves it along
+/// until it fades out.
+///
+/// There is no sound yet. When there is, it belongs in a system of its own
+/// reading the same `Collision` channel, next to this one and `ScoreSystem`.
+#[derive(Default)]
+pub struct ParticleSystem {
+    reader: Option<ReaderId<Collision>>,
+    texture: Option<TextureInfo>,
+}
+
+impl<'a> System<'a> for ParticleSystem {
+    type SystemData = (
+        Read<'a, EventChannel<Collision>>,
+        WriteStorage<'a, Particle>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Sprite>,
+        WriteExpect<'a, AssetManager>,
+        Read<'a, Time>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (collisions, mut particles, mut transforms, mut sprites, mut assets, time, entities): Self::SystemData,
+    ) {
+        let delta_t = time.delta_seconds();
+
+        for (p, t, e) in (&mut particles, &mut transforms, &entities).join() {
+            p.remaining -= delta_t;
+            if p.remaining <= 0.0 {
+                entities.delete(e).unwrap();
+                continue;
+            }
+            t.position += delta_t * p.velocity;
+            let size = PARTICLE_SCALE * p.remaining / PARTICLE_LIFETIME;
+            t.scale = (size, size).into();
+        }
+
+        let collisions: Vec<Collision> = collisions
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .copied()
+            .collect();
+        if collisions.is_empty() {
+            return;
+        }
+
+        let tex = *self.texture.get_or_insert_with(|| {
+            assets
+                .load_texture_image("resources/ball.png")
+                .expect("Failed to load particle texture")
+        });
+        let mut sprite = Sprite::new(&tex, (0, 0), (tex.width, tex.height));
+        sprite.offsets = [0.5, 0.5];
+
+        for collision in collisions {
+            // Drawn the same way as the ball that made them
+            let global = match transforms.get(collision.a) {
+                Some(t) => t.global,
+                None => continue,
+            };
+            for i in 0..PARTICLES_PER_COLLISION {
+                // Fanned out around the normal
+                let spread = 120.0 * (i as f32 + 0.5) / PARTICLES_PER_COLLISION as f32 - 60.0;
+                let direction = Basis2::from_angle(Deg(spread)).rotate_vector(collision.normal);
+
+                let mut t = Transform::default()
+                    .with_pos(collision.point)
+                    .with_scale((PARTICLE_SCALE, PARTICLE_SCALE));
+                t.global = global;
+                entities
+                    .build_entity()
+                    .with(t, &mut transforms)
+                    .with(sprite, &mut sprites)
+                    .with(
+                        Particle {
+                            velocity: PARTICLE_SPEED * direction,
+                            remaining: PARTICLE_LIFETIME,
+                        },
+                        &mut particles,
+                    )
+                    .build();
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<Collision>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_spawned_per_collision_and_fade_out() {
+        let mut world = World::new();
+        world.insert(AssetManager::new());
+        world.insert(Time::default());
+        let mut system = ParticleSystem::default();
+        System::setup(&mut system, &mut world);
+
+        let ball = world
+            .create_entity()
+            .with(Transform::default().with_pos((5., 5.)))
+            .build();
+        let block = world.create_entity().build();
+        world
+            .fetch_mut::<EventChannel<Collision>>()
+            .single_write(Collision {
+                a: ball,
+                b: block,
+                normal: (0., -1.).into(),
+                point: (5., 4.5).into(),
+            });
+
+        system.run_now(&world);
+        world.maintain();
+        assert_eq!(
+            world.read_storage::<Particle>().count(),
+            PARTICLES_PER_COLLISION
+        );
+
+        let steps = (PARTICLE_LIFETIME / world.fetch::<Time>().delta_seconds()).ceil() as usize;
+        for _ in 0..steps {
+            system.run_now(&world);
+            world.maintain();
+        }
+        assert_eq!(world.read_storage::<Particle>().count(), 0);
+    }
+}
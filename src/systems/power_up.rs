@@ -0,0 +1,182 @@
+use cgmath::Vector2;
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::asset_manager::AssetManager;
+use crate::collidable::{penetration, Collidable, Shape};
+use crate::components::{Collider, Paddle, PowerUp, PowerUpKind, Sprite, Transform};
+use crate::constants::{POWER_UP_DROP_CHANCE, POWER_UP_FALL_SPEED};
+use crate::resources::{Rng, Time};
+use crate::types::{GameEvent, TextureInfo};
+
+/// Drops the odd power-up where a block was destroyed, lets it fall, and
+/// sends `PowerUpCollected` when the paddle catches it.
+#[derive(Default)]
+pub struct PowerUpSystem {
+    reader: Option<ReaderId<GameEvent>>,
+    texture: Option<TextureInfo>,
+}
+
+impl<'a> System<'a> for PowerUpSystem {
+    type SystemData = (
+        Write<'a, EventChannel<GameEvent>>,
+        Write<'a, Rng>,
+        WriteStorage<'a, PowerUp>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, Sprite>,
+        WriteStorage<'a, Collider>,
+        ReadStorage<'a, Paddle>,
+        WriteExpect<'a, AssetManager>,
+        Read<'a, Time>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut events,
+            mut rng,
+            mut power_ups,
+            mut transforms,
+            mut sprites,
+            mut colliders,
+            paddles,
+            mut assets,
+            time,
+            entities,
+        ): Self::SystemData,
+    ) {
+        let delta_t = time.delta_seconds();
+
+        let destroyed: Vec<Vector2<f32>> = events
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .filter_map(|event| match event {
+                GameEvent::BlockDestroyed { position, .. } => Some(*position),
+                _ => None,
+            })
+            .collect();
+
+        let paddle = (&paddles, &transforms, &colliders)
+            .join()
+            .map(|(_, t, c)| (*t, *c))
+            .next();
+
+        let mut collected = vec![];
+        for (p, t, c, e) in (&power_ups, &mut transforms, &colliders, &entities).join() {
+            t.position.y -= POWER_UP_FALL_SPEED * delta_t;
+
+            let caught = paddle.is_some_and(|(paddle_t, paddle_c)| {
+                penetration(&c.shape(), t.position, &paddle_c.shape(), paddle_t.position).is_some()
+            });
+            if caught {
+                collected.push(GameEvent::PowerUpCollected(p.kind));
+                entities.delete(e).unwrap();
+            } else if c.bounds(t).1 .1 < 0.0 {
+                entities.delete(e).unwrap();
+            }
+        }
+        events.iter_write(collected);
+
+        // Drawn the same way as the paddle that is to catch them
+        let global = match paddle {
+            Some((t, _)) => t.global,
+            None => return,
+        };
+        for position in destroyed {
+            if rng.next_f32() >= POWER_UP_DROP_CHANCE {
+                continue;
+            }
+            let kind = PowerUpKind::ALL[rng.below(PowerUpKind::ALL.len())];
+
+            let tex = *self.texture.get_or_insert_with(|| {
+                assets
+                    .load_texture_image("resources/power_up.png")
+                    .expect("Failed to load power-up texture")
+            });
+            let mut t = Transform::default().with_pos(position);
+            t.global = global;
+            entities
+                .build_entity()
+                .with(t, &mut transforms)
+                .with(kind.sprite(&tex), &mut sprites)
+                .with(
+                    Collider::new(Shape::Aabb {
+                        half_width: 0.8,
+                        half_height: 0.4,
+                    }),
+                    &mut colliders,
+                )
+                .with(PowerUp { kind }, &mut power_ups)
+                .build();
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_up_caught_by_paddle() {
+        let mut world = World::new();
+        world.insert(AssetManager::new());
+        let mut system = PowerUpSystem::default();
+        System::setup(&mut system, &mut world);
+        let mut reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+
+        world
+            .create_entity()
+            .with(Paddle::new(4.))
+            .with(Transform::default().with_pos((10., 1.)))
+            .with(Paddle::collider(4.))
+            .build();
+        let shape = Shape::Aabb {
+            half_width: 0.8,
+            half_height: 0.4,
+        };
+        let caught = world
+            .create_entity()
+            .with(PowerUp {
+                kind: PowerUpKind::Laser,
+            })
+            .with(Transform::default().with_pos((11., 2.)))
+            .with(Collider::new(shape))
+            .build();
+        let missed = world
+            .create_entity()
+            .with(PowerUp {
+                kind: PowerUpKind::ExtraLife,
+            })
+            .with(Transform::default().with_pos((30., 2.)))
+            .with(Collider::new(shape))
+            .build();
+
+        for _ in 0..60 {
+            system.run_now(&world);
+            world.maintain();
+        }
+
+        assert!(!world.is_alive(caught));
+        assert!(!world.is_alive(missed));
+        let collected: Vec<PowerUpKind> = world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(&mut reader)
+            .filter_map(|event| match event {
+                GameEvent::PowerUpCollected(kind) => Some(*kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(collected, vec![PowerUpKind::Laser]);
+    }
+}
@@ -1,69 +1,32 @@
 #![allow(clippy::float_cmp)]
 
-use std::cell::RefCell;
-use std::fmt;
+use std::path::{Path, PathBuf};
 
 use cgmath::{ortho, Matrix4, SquareMatrix};
-use luminance::{
-    context::GraphicsContext as _,
-    linear::M44,
-    pipeline::{BoundTexture, PipelineState},
-    pixel::{NormRGB8UI, NormUnsigned},
-    render_state::RenderState,
-    shader::program::{Program, Uniform},
-    tess::{Mode, Tess, TessBuilder},
-    texture::{Dim2, Flat, GenMipmaps, Sampler, Texture},
-};
-use luminance_derive::UniformInterface;
-use luminance_glfw::{Action, GlfwSurface, Key, Surface as _, WindowDim, WindowEvent, WindowOpt};
 use specs::prelude::*;
 use specs::shrev::EventChannel;
 
 use crate::asset_manager::AssetManager;
 use crate::components::{Sprite, Transform};
-use crate::types::{GameEvent, InputEvent, ScreenContext, TextureId, VertexSemantics};
-
-const VS_STR: &str = include_str!("../vs.shader");
-const FS_STR: &str = include_str!("../fs.shader");
-
-#[derive(UniformInterface)]
-struct ShaderInterface {
-    #[uniform(unbound)]
-    world: Uniform<M44>,
-    #[uniform(unbound)]
-    model: Uniform<M44>,
-    #[uniform(unbound)]
-    view: Uniform<M44>,
-    #[uniform(unbound)]
-    image: Uniform<&'static BoundTexture<'static, Flat, Dim2, NormUnsigned>>,
-}
-
-struct RenderCommand {
-    tess: Tess,
-    model: Matrix4<f32>,
-    view: Matrix4<f32>,
-    texture: TextureId,
-}
-
-impl fmt::Debug for RenderCommand {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "RenderCommand {{ tex: {}, model: {:?} }}",
-            self.texture, self.model
-        )
-    }
-}
-
-pub struct RenderingSystem {
-    assets: Vec<Texture<Flat, Dim2, NormRGB8UI>>,
-    buf: RefCell<Vec<RenderCommand>>,
+use crate::constants::SCREENSHOT_DIR;
+use crate::game_error::GameError;
+use crate::renderer::{save_screenshot, DrawList, Renderer};
+use crate::types::{GameEvent, ScreenContext};
+
+/// Hands every sprite to a `Renderer` each frame, along with any textures
+/// loaded since the last one, and passes on the events of its window. On
+/// `Screenshot` the frame is also saved to `SCREENSHOT_DIR`.
+pub struct RenderingSystem<R> {
+    renderer: R,
+    /// Whether keys pressed in the window reach the game. Closing the window
+    /// always does.
+    live_input: bool,
+    reader: Option<ReaderId<GameEvent>>,
+    draw_list: DrawList,
     screen_context: ScreenContext,
-    program: Program<VertexSemantics, (), ShaderInterface>,
-    surface: RefCell<GlfwSurface>,
 }
 
-impl<'a> System<'a> for RenderingSystem {
+impl<'a, R: Renderer> System<'a> for RenderingSystem<R> {
     type SystemData = (
         ReadStorage<'a, Sprite>,
         ReadStorage<'a, Transform>,
@@ -75,69 +38,57 @@ impl<'a> System<'a> for RenderingSystem {
         &mut self,
         (sprites, transforms, mut event_channel, mut screen_ctx, mut asset_manager): Self::SystemData,
     ) {
-        let mut resize = false;
+        let screenshot = event_channel
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .any(|event| matches!(event, GameEvent::Screenshot));
+
         self.process_assets(&mut asset_manager);
-        for event in self.surface.borrow_mut().poll_events() {
-            match event {
-                WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
-                    event_channel.single_write(GameEvent::WindowEvent(event));
-                    event_channel.single_write(GameEvent::CloseWindow);
-                }
-                WindowEvent::FramebufferSize(..) => {
-                    resize = true;
-                }
-                WindowEvent::Key(k, _scancode, action, _mods) => {
-                    event_channel.single_write(GameEvent::Input(InputEvent::Key(k, action)))
-                }
-                _ => {}
-            }
+        let mut polled = self.renderer.poll_events();
+        if !self.live_input {
+            polled.retain(|event| !matches!(event, GameEvent::Input(_)));
         }
+        event_channel.iter_write(polled);
 
-        if resize {
-            let width = self.surface.borrow().width();
-            let height = self.surface.borrow().height();
+        let (width, height) = self.renderer.dimensions();
+        if (width, height) != self.screen_context.dimensions() {
             self.resize(width, height);
             screen_ctx.set_dimensions(self.screen_context.dimensions());
             screen_ctx.set_transform(self.screen_context.transform());
         }
-        for (sprite, transform) in (&sprites, &transforms).join() {
-            self.queue_sprite_render(&sprite, &transform);
-        }
 
-        self.render();
+        self.draw_list.rebuild((&sprites, &transforms).join());
+        // Reading the frame back draws it too
+        if screenshot {
+            match self.screenshot(SCREENSHOT_DIR) {
+                Ok(path) => println!("Saved {}", path.display()),
+                Err(e) => eprintln!("Could not save screenshot: {}", e),
+            }
+        } else {
+            self.renderer.render(&self.draw_list);
+        }
     }
 
     fn setup(&mut self, world: &mut World) {
         Self::SystemData::setup(world);
         self.process_assets(&mut world.fetch_mut::<AssetManager>());
         world.insert(self.screen_context);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
     }
 }
 
-impl RenderingSystem {
-    pub fn new(width: u32, height: u32) -> Self {
-        let surface = GlfwSurface::new(
-            WindowDim::Windowed(width, height),
-            "No Tilearino",
-            WindowOpt::default(),
-        )
-        .expect("unable to create surface");
-
-        let program = Program::<VertexSemantics, (), ShaderInterface>::from_strings(
-            None, VS_STR, None, FS_STR,
-        )
-        .expect("Could not create shader program");
-
-        if !program.warnings.is_empty() {
-            eprintln!("Warnings: {:?}", program.warnings);
-        }
-
+impl<R: Renderer> RenderingSystem<R> {
+    pub fn new(renderer: R) -> Self {
+        let (width, height) = renderer.dimensions();
         let screen_context = ScreenContext::new(Matrix4::<f32>::identity(), width, height);
         let mut s = RenderingSystem {
-            buf: RefCell::new(vec![]),
-            program: program.program,
-            surface: RefCell::new(surface),
-            assets: vec![],
+            renderer,
+            live_input: true,
+            reader: None,
+            draw_list: DrawList::default(),
             screen_context,
         };
 
@@ -145,51 +96,17 @@ impl RenderingSystem {
         s
     }
 
-    fn render(&self) {
-        let frame_buffer = self.surface.borrow_mut().back_buffer().unwrap();
-        self.surface.borrow_mut().pipeline_builder().pipeline(
-            &frame_buffer,
-            &PipelineState::default(),
-            |pipeline, mut shading_gate| {
-                for c in self.buf.borrow().iter() {
-                    let tex = self.assets.get(c.texture).unwrap();
-
-                    let bound_tex = pipeline.bind_texture(&tex);
-                    shading_gate.shade(&self.program, |iface, mut render_gate| {
-                        iface.world.update(self.screen_context.transform().into());
-                        iface.model.update(c.model.into());
-                        iface.view.update(c.view.into());
-                        iface.image.update(&bound_tex);
-
-                        render_gate.render(&RenderState::default(), |mut tess_gate| {
-                            tess_gate.render(&c.tess);
-                        });
-                    });
-                }
-            },
-        );
-
-        self.buf.borrow_mut().clear();
-        self.surface.borrow_mut().swap_buffers();
+    /// Drops key presses from the window when `false`, for when the input
+    /// comes from a replay instead.
+    pub fn with_live_input(mut self, live_input: bool) -> Self {
+        self.live_input = live_input;
+        self
     }
 
-    fn queue_sprite_render(&mut self, sprite: &Sprite, transform: &Transform) {
-        let tess = TessBuilder::new(self.surface.get_mut())
-            .add_vertices(sprite.get_vertices())
-            .set_mode(Mode::TriangleFan)
-            .build()
-            .unwrap();
-        let model = sprite.get_model_matrix();
-
-        let t = *transform;
-        let p = t.as_screen_point();
-
-        self.buf.borrow_mut().push(RenderCommand {
-            tess,
-            model,
-            view: t.with_pos((p.x, p.y)).matrix(),
-            texture: sprite.texture.id,
-        });
+    /// Draws the current frame, reads it back and saves it in `dir` as a PNG
+    /// named after the time. Returns the path it was saved to.
+    pub fn screenshot<P: AsRef<Path>>(&mut self, dir: P) -> Result<PathBuf, GameError> {
+        save_screenshot(&mut self.renderer, &self.draw_list, dir.as_ref())
     }
 
     fn resize(&mut self, width: u32, height: u32) {
@@ -202,18 +119,8 @@ impl RenderingSystem {
     }
 
     fn process_assets(&mut self, asset_manager: &mut AssetManager) {
-        {
-            let assets = &mut self.assets;
-            let surface = self.surface.get_mut();
-            asset_manager.upload_textures(|w, h, raw| {
-                let tex =
-                    Texture::<Flat, Dim2, NormRGB8UI>::new(surface, [w, h], 0, Sampler::default())
-                        .expect("luminance texture creation");
-
-                tex.upload_raw(GenMipmaps::No, raw.as_slice()).unwrap();
-                assets.push(tex);
-            });
-        }
+        let renderer = &mut self.renderer;
+        asset_manager.upload_textures(|w, h, raw| renderer.upload_texture(w, h, &raw));
     }
 }
 
@@ -221,8 +128,61 @@ impl RenderingSystem {
 mod tests {
     use super::*;
 
-    use crate::types::TextureInfo;
     use cgmath::Vector4;
+    use luminance_glfw::{Action, Key};
+
+    use crate::types::{InputEvent, TextureInfo};
+
+    /// A window where Left is held and then closed.
+    struct Window;
+
+    impl Renderer for Window {
+        fn upload_texture(&mut self, _: u32, _: u32, _: &[u8]) {}
+
+        fn render(&mut self, _: &DrawList) {}
+
+        fn dimensions(&self) -> (u32, u32) {
+            (800, 600)
+        }
+
+        fn read_pixels(&mut self, _: &DrawList) -> Vec<u8> {
+            vec![]
+        }
+
+        fn poll_events(&mut self) -> Vec<GameEvent> {
+            vec![
+                GameEvent::Input(InputEvent::Key(Key::Left, Action::Press)),
+                GameEvent::CloseWindow,
+            ]
+        }
+    }
+
+    #[test]
+    fn window_keys_dropped_without_live_input() {
+        let polled = |live_input| {
+            let mut world = World::new();
+            world.insert(AssetManager::new());
+            let mut system = RenderingSystem::new(Window).with_live_input(live_input);
+            System::setup(&mut system, &mut world);
+            let mut reader = world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader();
+
+            system.run_now(&world);
+            let events = world.fetch::<EventChannel<GameEvent>>();
+            events
+                .read(&mut reader)
+                .map(|event| match event {
+                    GameEvent::Input(_) => "input",
+                    GameEvent::CloseWindow => "close",
+                    _ => "other",
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(polled(true), vec!["input", "close"]);
+        assert_eq!(polled(false), vec!["close"]);
+    }
 
     #[test]
     fn test_sprite_offsets() {
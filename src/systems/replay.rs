@@ -0,0 +1,194 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write as _};
+use std::path::Path;
+use std::time::Duration;
+
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::game_error::GameError;
+use crate::replay::{Replay, ReplayEvent};
+use crate::resources::Time;
+use crate::types::{GameEvent, InputEvent};
+
+/// Appends every recordable key event to a replay file, tagged with the
+/// tick it is applied at. The first write that fails is reported and ends
+/// the recording, the game carries on without it.
+pub struct ReplayRecorderSystem<W: io::Write = File> {
+    reader: Option<ReaderId<GameEvent>>,
+    /// `None` once writing has failed.
+    out: Option<BufWriter<W>>,
+    name: String,
+}
+
+impl ReplayRecorderSystem<File> {
+    pub fn create<P: AsRef<Path>>(path: P, fixed_step: Duration) -> Result<Self, GameError> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| GameError(format!("failed to create replay {}: {}", path.display(), e)))?;
+        ReplayRecorderSystem::new(file, path.display().to_string(), fixed_step)
+            .map_err(|e| GameError(format!("failed to write replay {}: {}", path.display(), e)))
+    }
+}
+
+impl<W: io::Write> ReplayRecorderSystem<W> {
+    /// Records into `out`, calling it `name` in error messages.
+    pub fn new(out: W, name: String, fixed_step: Duration) -> io::Result<Self> {
+        let mut out = BufWriter::new(out);
+        out.write_all(Replay::header(fixed_step).as_bytes())?;
+
+        Ok(ReplayRecorderSystem {
+            reader: None,
+            out: Some(out),
+            name,
+        })
+    }
+}
+
+impl<'a, W: io::Write> System<'a> for ReplayRecorderSystem<W> {
+    type SystemData = (Read<'a, EventChannel<GameEvent>>, Read<'a, Time>);
+
+    fn run(&mut self, (events, time): Self::SystemData) {
+        let r = self.reader.as_mut().expect("Event reader uninitialized");
+        let out = match &mut self.out {
+            Some(out) => out,
+            None => {
+                // Still read, so the channel doesn't keep the events for us
+                events.read(r).for_each(drop);
+                return;
+            }
+        };
+
+        let mut result = Ok(());
+        for event in events.read(r) {
+            if let GameEvent::Input(InputEvent::Key(key, action)) = event {
+                if !ReplayEvent::is_recordable(*key) {
+                    continue;
+                }
+                let event = ReplayEvent {
+                    tick: time.tick(),
+                    key: *key,
+                    action: *action,
+                };
+                result = result.and_then(|_| writeln!(out, "{}", event));
+            }
+        }
+        if let Err(e) = result.and_then(|_| out.flush()) {
+            eprintln!("Stopped recording replay {}: {}", self.name, e);
+            self.out = None;
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Feeds a recorded replay back into the event channel. Must run after
+/// `TimeSystem` and before `InputSystem`.
+pub struct ReplayPlayerSystem {
+    events: VecDeque<ReplayEvent>,
+}
+
+impl ReplayPlayerSystem {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayerSystem {
+            events: replay.events.into(),
+        }
+    }
+}
+
+impl<'a> System<'a> for ReplayPlayerSystem {
+    type SystemData = (Write<'a, EventChannel<GameEvent>>, Read<'a, Time>);
+
+    fn run(&mut self, (mut events, time): Self::SystemData) {
+        while let Some(event) = self.events.front() {
+            if event.tick > time.tick() {
+                break;
+            }
+            events.single_write(GameEvent::Input(InputEvent::Key(event.key, event.action)));
+            self.events.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use luminance_glfw::{Action, Key};
+
+    /// A disk that is always full.
+    struct Full;
+
+    impl io::Write for Full {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorder_stops_on_write_error() {
+        let mut world = World::new();
+        world.insert(EventChannel::<GameEvent>::new());
+        let step = Duration::from_millis(10);
+        world.insert(Time::new(step));
+
+        let mut recorder = ReplayRecorderSystem::new(Full, "full".to_string(), step).unwrap();
+        System::setup(&mut recorder, &mut world);
+        for _ in 0..2 {
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .single_write(GameEvent::Input(InputEvent::Key(Key::Left, Action::Press)));
+            recorder.run_now(&world);
+        }
+
+        assert!(recorder.out.is_none());
+    }
+
+    #[test]
+    fn player_writes_events_at_their_tick() {
+        let mut world = World::new();
+        let mut reader = {
+            let mut ch = EventChannel::<GameEvent>::new();
+            let reader = ch.register_reader();
+            world.insert(ch);
+            reader
+        };
+        let step = Duration::from_millis(10);
+        world.insert(Time::new(step));
+
+        let mut replay = Replay::new(step);
+        replay.events.push(ReplayEvent {
+            tick: 2,
+            key: Key::Right,
+            action: Action::Press,
+        });
+        let mut player = ReplayPlayerSystem::new(replay);
+
+        let mut seen = vec![];
+        for _ in 0..4 {
+            world.fetch_mut::<Time>().advance(step);
+            player.run_now(&world);
+            let tick = world.fetch::<Time>().tick();
+            for event in world.fetch::<EventChannel<GameEvent>>().read(&mut reader) {
+                if let GameEvent::Input(InputEvent::Key(Key::Right, Action::Press)) = event {
+                    seen.push(tick);
+                }
+            }
+            world.fetch_mut::<Time>().complete_step();
+        }
+
+        assert_eq!(seen, vec![2]);
+    }
+}
@@ -0,0 +1,107 @@
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::Paddle;
+use crate::resources::Score;
+use crate::types::{Collision, GameEvent};
+
+/// Adds up the points of destroyed blocks. The combo is broken whenever a
+/// ball touches the paddle or is lost.
+#[derive(Default)]
+pub struct ScoreSystem {
+    reader: Option<ReaderId<GameEvent>>,
+    collision_reader: Option<ReaderId<Collision>>,
+}
+
+impl<'a> System<'a> for ScoreSystem {
+    type SystemData = (
+        Read<'a, EventChannel<GameEvent>>,
+        Read<'a, EventChannel<Collision>>,
+        ReadStorage<'a, Paddle>,
+        Write<'a, Score>,
+    );
+
+    fn run(&mut self, (events, collisions, paddles, mut score): Self::SystemData) {
+        let r = self.reader.as_mut().expect("Event reader uninitialized");
+        for event in events.read(r) {
+            match event {
+                GameEvent::BlockDestroyed { points, .. } => score.add_block(*points),
+                GameEvent::BallLost(_) => score.reset_combo(),
+                _ => {}
+            }
+        }
+
+        let r = self
+            .collision_reader
+            .as_mut()
+            .expect("Event reader uninitialized");
+        if collisions.read(r).any(|c| paddles.contains(c.b)) {
+            score.reset_combo();
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+        self.collision_reader = Some(
+            world
+                .fetch_mut::<EventChannel<Collision>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::Vector2;
+
+    use crate::components::BlockKind;
+
+    #[test]
+    fn score_follows_events() {
+        let mut world = World::new();
+        let mut system = ScoreSystem::default();
+        System::setup(&mut system, &mut world);
+
+        let ball = world.create_entity().build();
+        let block = world.create_entity().build();
+        let paddle = world.create_entity().with(Paddle::default()).build();
+        let destroyed = || GameEvent::BlockDestroyed {
+            entity: block,
+            position: Vector2::new(0., 0.),
+            kind: BlockKind::Standard,
+            points: 10,
+        };
+
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .iter_write(vec![destroyed(), destroyed()]);
+        system.run_now(&world);
+
+        world
+            .fetch_mut::<EventChannel<Collision>>()
+            .single_write(Collision {
+                a: ball,
+                b: paddle,
+                normal: Vector2::new(0., 1.),
+                point: Vector2::new(0., 0.),
+            });
+        system.run_now(&world);
+
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(destroyed());
+        system.run_now(&world);
+
+        let score = world.fetch::<Score>();
+        assert_eq!(score.points, 10 + 20 + 10);
+        assert_eq!(score.combo, 1);
+        assert_eq!(score.best_combo, 2);
+    }
+}
@@ -0,0 +1,267 @@
+use luminance_glfw::{Action, Key};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+
+use crate::components::Stuck;
+use crate::resources::{GameState, StateStack, Time};
+use crate::types::{GameEvent, InputEvent};
+
+/// What the state machine reacts to, distilled from `GameEvent`s.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Trigger {
+    Confirm,
+    TogglePause,
+    StepOnce,
+    LevelComplete,
+    GameOver,
+}
+
+impl Trigger {
+    fn from_event(event: &GameEvent) -> Option<Trigger> {
+        match event {
+            GameEvent::Input(InputEvent::Key(Key::Enter, Action::Press))
+            | GameEvent::Input(InputEvent::Key(Key::Space, Action::Press)) => {
+                Some(Trigger::Confirm)
+            }
+            GameEvent::TogglePause => Some(Trigger::TogglePause),
+            GameEvent::StepOnce => Some(Trigger::StepOnce),
+            GameEvent::LevelComplete(_) => Some(Trigger::LevelComplete),
+            GameEvent::GameOver | GameEvent::AllLevelsComplete => Some(Trigger::GameOver),
+            _ => None,
+        }
+    }
+}
+
+/// Moves between game states in response to events, and keeps the
+/// simulation clock stopped outside of `Playing`. While `Paused` the clock
+/// can still be let through one step at a time.
+///
+/// While playing, the same keys launch the ball off the paddle. With
+/// `auto_advance` the title screen and level complete screens are skipped,
+/// and balls waiting on the paddle are launched instead of waiting for a key
+/// press.
+pub struct StateSystem {
+    reader: Option<ReaderId<GameEvent>>,
+    auto_advance: bool,
+}
+
+impl StateSystem {
+    pub fn new(auto_advance: bool) -> Self {
+        StateSystem {
+            reader: None,
+            auto_advance,
+        }
+    }
+}
+
+impl<'a> System<'a> for StateSystem {
+    type SystemData = (
+        Write<'a, EventChannel<GameEvent>>,
+        Write<'a, StateStack>,
+        Write<'a, Time>,
+        ReadStorage<'a, Stuck>,
+    );
+
+    fn run(&mut self, (mut events, mut states, mut time, stuck): Self::SystemData) {
+        let triggers: Vec<Trigger> = events
+            .read(self.reader.as_mut().expect("Event reader uninitialized"))
+            .filter_map(Trigger::from_event)
+            .collect();
+
+        let before = states.current();
+        let mut out = vec![];
+        let mut step_once = false;
+
+        for trigger in triggers {
+            match (states.current(), trigger) {
+                (GameState::Title, Trigger::Confirm) => states.set(GameState::Playing),
+                (GameState::Playing, Trigger::Confirm) => out.push(GameEvent::Launch),
+                (GameState::Playing, Trigger::TogglePause) => states.push(GameState::Paused),
+                (GameState::Paused, Trigger::TogglePause) => {
+                    states.pop();
+                }
+                (GameState::Paused, Trigger::StepOnce) => step_once = true,
+                (GameState::Playing, Trigger::LevelComplete) => {
+                    states.push(GameState::LevelComplete)
+                }
+                (GameState::LevelComplete, Trigger::Confirm) => {
+                    states.pop();
+                    out.push(GameEvent::NextLevel);
+                }
+                (GameState::GameOver, Trigger::Confirm) => {
+                    states.set(GameState::Title);
+                    out.push(GameEvent::NewGame);
+                }
+                (GameState::GameOver, Trigger::GameOver) => {}
+                (_, Trigger::GameOver) => states.set(GameState::GameOver),
+                _ => {}
+            }
+        }
+
+        if self.auto_advance {
+            match states.current() {
+                GameState::Title => states.set(GameState::Playing),
+                GameState::LevelComplete => {
+                    states.pop();
+                    out.push(GameEvent::NextLevel);
+                }
+                GameState::Playing if (&stuck).join().next().is_some() => {
+                    out.push(GameEvent::Launch)
+                }
+                _ => {}
+            }
+        }
+
+        if states.current() != before {
+            out.push(GameEvent::StateChanged(states.current()));
+        }
+        time.set_paused(!states.current().runs_simulation());
+        if step_once && states.current() == GameState::Paused {
+            time.step_once();
+        }
+        events.iter_write(out);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_world(auto_advance: bool) -> (World, StateSystem, ReaderId<GameEvent>) {
+        let mut world = World::new();
+        let mut system = StateSystem::new(auto_advance);
+        System::setup(&mut system, &mut world);
+        let reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        (world, system, reader)
+    }
+
+    fn send(world: &World, system: &mut StateSystem, event: GameEvent) -> GameState {
+        world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .single_write(event);
+        system.run_now(world);
+        world.fetch::<StateStack>().current()
+    }
+
+    fn confirm() -> GameEvent {
+        GameEvent::Input(InputEvent::Key(Key::Enter, Action::Press))
+    }
+
+    #[test]
+    fn state_full_game() {
+        let (world, mut system, mut reader) = state_world(false);
+
+        assert_eq!(send(&world, &mut system, confirm()), GameState::Playing);
+        assert!(!world.fetch::<Time>().paused());
+        assert_eq!(send(&world, &mut system, confirm()), GameState::Playing);
+
+        assert_eq!(
+            send(&world, &mut system, GameEvent::TogglePause),
+            GameState::Paused
+        );
+        assert!(world.fetch::<Time>().paused());
+        assert_eq!(
+            send(&world, &mut system, GameEvent::TogglePause),
+            GameState::Playing
+        );
+
+        assert_eq!(
+            send(&world, &mut system, GameEvent::LevelComplete(1)),
+            GameState::LevelComplete
+        );
+        assert!(world.fetch::<Time>().paused());
+        assert_eq!(send(&world, &mut system, confirm()), GameState::Playing);
+
+        assert_eq!(
+            send(&world, &mut system, GameEvent::GameOver),
+            GameState::GameOver
+        );
+        assert_eq!(send(&world, &mut system, confirm()), GameState::Title);
+
+        let requests: Vec<&'static str> = world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(&mut reader)
+            .filter_map(|e| match e {
+                GameEvent::Launch => Some("launch"),
+                GameEvent::NextLevel => Some("next"),
+                GameEvent::NewGame => Some("new"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(requests, vec!["launch", "next", "new"]);
+    }
+
+    #[test]
+    fn state_step_once_only_when_paused() {
+        let (world, mut system, _) = state_world(false);
+        send(&world, &mut system, confirm());
+
+        send(&world, &mut system, GameEvent::StepOnce);
+        assert_eq!(world.fetch::<Time>().steps(), 0);
+
+        send(&world, &mut system, GameEvent::TogglePause);
+        assert!(!world.fetch::<Time>().can_step());
+        assert_eq!(
+            send(&world, &mut system, GameEvent::StepOnce),
+            GameState::Paused
+        );
+        assert!(world.fetch::<Time>().can_step());
+        assert_eq!(world.fetch::<Time>().steps(), 1);
+    }
+
+    #[test]
+    fn state_pause_ignored_outside_play() {
+        let (world, mut system, _) = state_world(false);
+        assert_eq!(
+            send(&world, &mut system, GameEvent::TogglePause),
+            GameState::Title
+        );
+    }
+
+    #[test]
+    fn state_auto_advance_launches_stuck_balls() {
+        let (mut world, mut system, mut reader) = state_world(true);
+        let launches = |world: &World, reader: &mut ReaderId<GameEvent>| {
+            world
+                .fetch::<EventChannel<GameEvent>>()
+                .read(reader)
+                .filter(|e| matches!(e, GameEvent::Launch))
+                .count()
+        };
+
+        system.run_now(&world);
+        system.run_now(&world);
+        assert_eq!(launches(&world, &mut reader), 0);
+
+        let ball = world.create_entity().with(Stuck { offset: 0. }).build();
+        system.run_now(&world);
+        assert_eq!(launches(&world, &mut reader), 1);
+
+        world.delete_entity(ball).unwrap();
+        system.run_now(&world);
+        assert_eq!(launches(&world, &mut reader), 0);
+    }
+
+    #[test]
+    fn state_auto_advance() {
+        let (world, mut system, _) = state_world(true);
+        system.run_now(&world);
+        assert_eq!(world.fetch::<StateStack>().current(), GameState::Playing);
+
+        assert_eq!(
+            send(&world, &mut system, GameEvent::LevelComplete(1)),
+            GameState::Playing
+        );
+    }
+}
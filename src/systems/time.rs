@@ -0,0 +1,45 @@
+use std::time::Instant;
+
+use specs::prelude::*;
+
+use crate::resources::Time;
+
+/// Feeds elapsed time into the `Time` resource at the start of every frame.
+pub struct TimeSystem {
+    last_called: Option<Instant>,
+    lockstep: bool,
+}
+
+impl TimeSystem {
+    /// Advance by the wall-clock time between frames.
+    pub fn real() -> Self {
+        TimeSystem {
+            last_called: None,
+            lockstep: false,
+        }
+    }
+
+    /// Advance by exactly one fixed step per frame, regardless of how long
+    /// the frame took. Used when there is no display to keep pace with.
+    pub fn lockstep() -> Self {
+        TimeSystem {
+            last_called: None,
+            lockstep: true,
+        }
+    }
+}
+
+impl<'a> System<'a> for TimeSystem {
+    type SystemData = Write<'a, Time>;
+
+    fn run(&mut self, mut time: Self::SystemData) {
+        let now = Instant::now();
+        let elapsed = if self.lockstep {
+            time.fixed_step()
+        } else {
+            self.last_called.map(|last| now - last).unwrap_or_default()
+        };
+        time.advance(elapsed);
+        self.last_called = Some(now);
+    }
+}
@@ -1,7 +1,11 @@
-use cgmath::Matrix4;
+use cgmath::{Matrix4, Vector2};
 use luminance_derive::{Semantics, Vertex};
 use luminance_glfw::{Action, Key, WindowEvent};
 
+use crate::components::{BlockKind, PowerUpKind};
+use crate::resources::GameState;
+use specs::Entity;
+
 pub type TextureId = usize;
 
 #[derive(Copy, Clone)]
@@ -45,6 +49,43 @@ pub enum GameEvent {
     CloseWindow,
     WindowEvent(WindowEvent),
     Input(InputEvent),
+    /// A level has been loaded, with its 1-based number.
+    LevelStarted(usize),
+    /// Every destructible block of the numbered level is gone.
+    LevelComplete(usize),
+    /// The last level was finished and there is no next one.
+    AllLevelsComplete,
+    /// The ball fell past the bottom of the world.
+    BallLost(Entity),
+    /// A life was taken, with the number of lives left.
+    LifeLost(u32),
+    /// No lives are left.
+    GameOver,
+    /// Asks for the game to be paused or resumed.
+    TogglePause,
+    /// Asks for exactly one simulation step while paused.
+    StepOnce,
+    /// Asks for the balls resting on the paddle to be launched.
+    Launch,
+    /// Asks for the next frame to be saved as a PNG.
+    Screenshot,
+    /// The top of the state stack changed.
+    StateChanged(GameState),
+    /// Asks for the next level to be loaded.
+    NextLevel,
+    /// Asks for a fresh game from the first level.
+    NewGame,
+    /// A block took its last hit. `position` is in world units.
+    BlockDestroyed {
+        entity: Entity,
+        position: Vector2<f32>,
+        kind: BlockKind,
+        points: u32,
+    },
+    /// The paddle caught a falling power-up.
+    PowerUpCollected(PowerUpKind),
+    /// A timed power-up ran out.
+    PowerUpExpired(PowerUpKind),
 }
 
 #[derive(Copy, Clone)]
@@ -81,10 +122,31 @@ impl ScreenContext {
     }
 }
 
-#[derive(PartialEq, Debug)]
-pub enum OverlapType {
-    OnlyX,
-    OnlyY,
-    Both,
-    None,
+/// Something moving, `a`, ran into `b`: a ball bouncing off it or a laser
+/// bolt hitting it. `normal` points out of `b` towards `a` and `point` is
+/// where they touched, both in world units.
+#[derive(Copy, Clone, Debug)]
+pub struct Collision {
+    pub a: Entity,
+    pub b: Entity,
+    pub normal: Vector2<f32>,
+    pub point: Vector2<f32>,
+}
+
+/// Where a moving shape first touches another one.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Contact {
+    /// Fraction of the movement travelled before touching, from 0 to 1.
+    pub time: f32,
+    /// Unit normal of the face that was hit, pointing away from it.
+    pub normal: Vector2<f32>,
+}
+
+/// How far one shape has sunk into another.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Penetration {
+    /// Unit normal to move shape `a` along to separate them.
+    pub normal: Vector2<f32>,
+    /// Distance to move it, in world units.
+    pub depth: f32,
 }
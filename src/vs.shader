@@ -1,14 +1,12 @@
-// those are our vertex attributes
+// those are our vertex attributes, already in screen pixels
 in vec2 position;
 in vec2 texture_coords;
 
-uniform mat4 model;
-uniform mat4 view;
 uniform mat4 world;
 
 out vec2 TexCoords;
 
 void main() {
   TexCoords = texture_coords;
-  gl_Position =  world * view * model * vec4(position, 0., 1.);
+  gl_Position =  world * vec4(position, 0., 1.);
 }